@@ -1,4 +1,4 @@
-use std::{io, time::SystemTime};
+use std::io;
 
 use log::{debug, info, trace, warn};
 
@@ -26,7 +26,7 @@ fn setup_logging(verbosity: u64) -> Result<(), fern::InitError> {
         .format(|out, message, record| {
             out.finish(format_args!(
                 "[{} {} {}] {}",
-                humantime::format_rfc3339_seconds(SystemTime::now()),
+                fern::time::Rfc3339::new().now(),
                 record.level(),
                 record.target(),
                 message
@@ -40,13 +40,13 @@ fn setup_logging(verbosity: u64) -> Result<(), fern::InitError> {
             if record.level() > log::LevelFilter::Info && record.target() == "cmd_program" {
                 out.finish(format_args!(
                     "DEBUG @ {}: {}",
-                    humantime::format_rfc3339_seconds(SystemTime::now()),
+                    fern::time::Rfc3339::new().now(),
                     message
                 ))
             } else {
                 out.finish(format_args!(
                     "[{} {} {}] {}",
-                    humantime::format_rfc3339_seconds(SystemTime::now()),
+                    fern::time::Rfc3339::new().now(),
                     record.level(),
                     record.target(),
                     message
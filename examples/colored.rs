@@ -1,5 +1,3 @@
-use std::time::SystemTime;
-
 use fern::colors::{Color, ColoredLevelConfig};
 use log::{debug, error, warn};
 
@@ -11,7 +9,7 @@ fn main() {
         .format(move |out, message, record| {
             out.finish(format_args!(
                 "[{} {} {}] {}",
-                humantime::format_rfc3339_seconds(SystemTime::now()),
+                fern::time::Rfc3339::new().now(),
                 // This will color the log level only, not the whole line. Just a touch.
                 colors.color(record.level()),
                 record.target(),
@@ -6,7 +6,6 @@
 //!   line is white
 //! - when the log level is debug, the whole line is white
 //! - when the log level is trace, the whole line is gray ("bright black")
-use std::time::SystemTime;
 
 use fern::colors::{Color, ColoredLevelConfig};
 use log::{debug, error, info, trace, warn};
@@ -60,15 +59,17 @@ fn set_up_logging() {
     fern::Dispatch::new()
         .format(move |out, message, record| {
             out.finish(format_args!(
-                "{color_line}[{date} {level} {target} {color_line}] {message}\x1B[0m",
-                color_line = format_args!(
-                    "\x1B[{}m",
-                    colors_line.get_color(&record.level()).to_fg_str()
+                "{}",
+                colors_line.color_line(
+                    record.level(),
+                    format_args!(
+                        "[{date} {level} {target}] {message}",
+                        date = fern::time::Rfc3339::new().now(),
+                        target = record.target(),
+                        level = colors_level.color(record.level()),
+                        message = message,
+                    ),
                 ),
-                date = humantime::format_rfc3339_seconds(SystemTime::now()),
-                target = record.target(),
-                level = colors_level.color(record.level()),
-                message = message,
             ));
         })
         // set the default log level. to filter out verbose log messages from dependencies, set
@@ -2,12 +2,19 @@ use std::{
     borrow::Cow,
     cmp, fmt, fs, io,
     io::Write,
-    sync::{mpsc::Sender, Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc, Mutex, RwLock,
+    },
 };
 
-#[cfg(feature = "date-based")]
+#[cfg(any(feature = "date-based", all(target_os = "linux", feature = "journald")))]
 use std::path::{Path, PathBuf};
 
+#[cfg(all(target_os = "linux", feature = "journald"))]
+use std::os::unix::net::UnixDatagram;
+
 #[cfg(all(not(windows), any(feature = "syslog-4", feature = "syslog-6")))]
 use std::collections::HashMap;
 
@@ -16,7 +23,21 @@ use std::collections::BTreeMap;
 
 use log::Log;
 
-use crate::{log_impl, Filter, FormatCallback, Formatter};
+use crate::{log_impl, Filter, FormatCallback, Formatter, MemoryBuffer, Redactor};
+
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+use crate::SyslogSeverityFn;
+
+#[cfg(feature = "kv")]
+use crate::FieldsFn;
 
 #[cfg(feature = "date-based")]
 use crate::log_impl::DateBasedState;
@@ -105,11 +126,19 @@ use crate::{Syslog7Rfc3164Logger, Syslog7Rfc5424Logger, Syslog7TransformFn};
 /// ```
 #[must_use = "this is only a logger configuration and must be consumed with into_log() or apply()"]
 pub struct Dispatch {
-    format: Option<Box<Formatter>>,
+    format: Vec<Box<Formatter>>,
     children: Vec<OutputInner>,
     default_level: log::LevelFilter,
     levels: Vec<(Cow<'static, str>, log::LevelFilter)>,
     filters: Vec<Box<Filter>>,
+    render_once: bool,
+    multiline: Option<Multiline>,
+    redactor: Option<Redactor>,
+    max_message_len: Option<usize>,
+    #[cfg(feature = "kv")]
+    fields: Vec<(Cow<'static, str>, String)>,
+    #[cfg(feature = "kv")]
+    fields_from: Vec<Box<FieldsFn>>,
 }
 
 /// Logger which is usable as an output for multiple other loggers.
@@ -132,11 +161,19 @@ impl Dispatch {
     #[inline]
     pub fn new() -> Self {
         Dispatch {
-            format: None,
+            format: Vec::new(),
             children: Vec::new(),
             default_level: log::LevelFilter::Trace,
             levels: Vec::new(),
             filters: Vec::new(),
+            render_once: false,
+            multiline: None,
+            redactor: None,
+            max_message_len: None,
+            #[cfg(feature = "kv")]
+            fields: Vec::new(),
+            #[cfg(feature = "kv")]
+            fields_from: Vec::new(),
         }
     }
 
@@ -144,6 +181,9 @@ impl Dispatch {
     /// callback, a message and a log record, and write the resulting
     /// format to the writer.
     ///
+    /// This replaces any formatters previously set or added with
+    /// [`Dispatch::then_format`].
+    ///
     /// The log record is passed for completeness, but the `args()` method of
     /// the record should be ignored, and the [`fmt::Arguments`] given
     /// should be used instead. `record.args()` may be used to retrieve the
@@ -159,6 +199,7 @@ impl Dispatch {
     /// the logger chain unformatted.
     ///
     /// [`fmt::Arguments`]: https://doc.rust-lang.org/std/fmt/struct.Arguments.html
+    /// [`Dispatch::then_format`]: #method.then_format
     ///
     /// Example usage:
     ///
@@ -178,7 +219,58 @@ impl Dispatch {
     where
         F: Fn(FormatCallback, &fmt::Arguments, &log::Record) + Sync + Send + 'static,
     {
-        self.format = Some(Box::new(formatter));
+        self.format.clear();
+        self.format.push(Box::new(formatter));
+        self
+    }
+
+    /// Adds a formatting stage, run after any formatters previously set or
+    /// added to this dispatch.
+    ///
+    /// Each stage receives the message produced by the previous stage through
+    /// its [`FormatCallback`], and the record with that message as its
+    /// `args()`. A stage which doesn't call `out.finish` passes the message
+    /// on unchanged.
+    ///
+    /// This allows splitting formatting into reusable pieces. Stages written
+    /// as functions, or closures implementing `Clone`, can be added to any
+    /// number of dispatches.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// fn with_target(out: fern::FormatCallback, message: &std::fmt::Arguments, record: &log::Record) {
+    ///     out.finish(format_args!("{}: {}", record.target(), message))
+    /// }
+    ///
+    /// fn with_level(out: fern::FormatCallback, message: &std::fmt::Arguments, record: &log::Record) {
+    ///     out.finish(format_args!("[{}] {}", record.level(), message))
+    /// }
+    ///
+    /// fern::Dispatch::new()
+    ///     .chain(
+    ///         // "[INFO] my_app: message"
+    ///         fern::Dispatch::new()
+    ///             .format(with_target)
+    ///             .then_format(with_level)
+    ///             .chain(std::io::stdout()),
+    ///     )
+    ///     .chain(
+    ///         // "[INFO] message"
+    ///         fern::Dispatch::new()
+    ///             .then_format(with_level)
+    ///             .chain(std::io::stderr()),
+    ///     )
+    ///     # .into_log();
+    /// ```
+    ///
+    /// [`FormatCallback`]: struct.FormatCallback.html
+    #[inline]
+    pub fn then_format<F>(mut self, formatter: F) -> Self
+    where
+        F: Fn(FormatCallback, &fmt::Arguments, &log::Record) + Sync + Send + 'static,
+    {
+        self.format.push(Box::new(formatter));
         self
     }
 
@@ -353,6 +445,216 @@ impl Dispatch {
         self
     }
 
+    /// Adds a key-value to every record passing through this logger.
+    ///
+    /// The field is visible to this logger's formatter and all outputs
+    /// through the record's [`key_values`], after the record's own
+    /// key-values. This is useful for tagging records with process-wide data,
+    /// such as the service name or version, for structured outputs like
+    /// [`Journald`] or syslog's RFC 5424 structured data.
+    ///
+    /// Adding a field with the same key again replaces its value.
+    ///
+    /// Requires the `kv` feature.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// fern::Dispatch::new()
+    ///     .with_field("service", "billing")
+    ///     .with_field("version", env!("CARGO_PKG_VERSION"))
+    ///     .with_field("pid", std::process::id())
+    ///     .format(|out, message, record| {
+    ///         let service = record.key_values().get("service".into());
+    ///         out.finish(format_args!("[{}] {}", service.unwrap(), message))
+    ///     })
+    ///     .chain(std::io::stdout())
+    ///     # .into_log();
+    /// ```
+    ///
+    /// [`key_values`]: https://docs.rs/log/0.4/log/struct.Record.html#method.key_values
+    /// [`Journald`]: struct.Journald.html
+    #[cfg(feature = "kv")]
+    pub fn with_field<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: fmt::Display,
+    {
+        let key = key.into();
+        let value = value.to_string();
+
+        if let Some((_, existing)) = self.fields.iter_mut().find(|(name, _)| *name == key) {
+            *existing = value;
+        } else {
+            self.fields.push((key, value));
+        }
+        self
+    }
+
+    /// Adds key-values computed by `f` to every record passing through this
+    /// logger.
+    ///
+    /// `f` is called once, when the first record passes through the logger,
+    /// and the result is reused afterwards. Records logged while `f` runs,
+    /// including by `f` itself, don't include these fields.
+    ///
+    /// These fields come after those added with [`Dispatch::with_field`],
+    /// replacing any with the same key.
+    ///
+    /// Requires the `kv` feature.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// fern::Dispatch::new()
+    ///     .with_fields_from(|| {
+    ///         vec![(
+    ///             "environment",
+    ///             std::env::var("APP_ENV").unwrap_or_else(|_| "development".into()),
+    ///         )]
+    ///     })
+    ///     .chain(std::io::stdout())
+    ///     # .into_log();
+    /// ```
+    ///
+    /// [`Dispatch::with_field`]: #method.with_field
+    #[cfg(feature = "kv")]
+    pub fn with_fields_from<F, I, K, V>(mut self, f: F) -> Self
+    where
+        F: Fn() -> I + Send + Sync + 'static,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<Cow<'static, str>>,
+        V: fmt::Display,
+    {
+        self.fields_from.push(Box::new(move || {
+            f().into_iter()
+                .map(|(key, value)| (key.into(), value.to_string()))
+                .collect()
+        }));
+        self
+    }
+
+    /// Sets whether to render each message once, before passing it to this
+    /// dispatch's outputs.
+    ///
+    /// By default, each output renders the message itself, so a message with
+    /// expensive [`Display`] implementations is rendered once per output.
+    /// When enabled, the final message is rendered into a reused thread-local
+    /// buffer, and all outputs receive the rendered text instead.
+    ///
+    /// Since the message is rendered before any output is locked, this also
+    /// prevents stuttering when formatting the message itself logs, similarly
+    /// to the `meta-logging-in-format` feature.
+    ///
+    /// Default is `false`.
+    ///
+    /// [`Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+    ///
+    /// Example usage:
+    ///
+    /// ```no_run
+    /// # fn setup_logger() -> Result<(), fern::InitError> {
+    /// fern::Dispatch::new()
+    ///     .render_once(true)
+    ///     .chain(std::io::stdout())
+    ///     .chain(fern::log_file("output.log")?)
+    ///     .chain(fern::log_file("output-copy.log")?)
+    ///     # .into_log();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn render_once(mut self, enabled: bool) -> Self {
+        self.render_once = enabled;
+        self
+    }
+
+    /// Sets how messages containing newlines are handled.
+    ///
+    /// The policy is applied before this dispatch's formatter runs. With
+    /// [`Multiline::RepeatHeader`], the formatter runs once for each line,
+    /// and each line is logged separately.
+    ///
+    /// To apply a policy to a single output, see [`Output::multiline`].
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// fern::Dispatch::new()
+    ///     .multiline(fern::Multiline::RepeatHeader)
+    ///     .format(|out, message, record| {
+    ///         out.finish(format_args!("[{}] {}", record.level(), message))
+    ///     })
+    ///     // "[ERROR] first line\n[ERROR] second line\n"
+    ///     .chain(std::io::stdout())
+    ///     # .into_log();
+    /// ```
+    ///
+    /// [`Multiline::RepeatHeader`]: enum.Multiline.html#variant.RepeatHeader
+    /// [`Output::multiline`]: struct.Output.html#method.multiline
+    #[inline]
+    pub fn multiline(mut self, policy: Multiline) -> Self {
+        self.multiline = Some(policy);
+        self
+    }
+
+    /// Sets a redactor removing secrets from records before any output sees
+    /// them.
+    ///
+    /// The redactor is applied to the message after all formatting stages
+    /// ran, and, with the `kv` feature, to each key-value. Messages are
+    /// rendered to a `String` once for redaction, as with
+    /// [`Dispatch::render_once`].
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// fern::Dispatch::new()
+    ///     .redact(fern::Redactor::new())
+    ///     // "login with password=[REDACTED]"
+    ///     .chain(std::io::stdout())
+    ///     # .into_log();
+    /// ```
+    ///
+    /// [`Dispatch::render_once`]: #method.render_once
+    #[inline]
+    pub fn redact(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Sets the maximum length of messages, in bytes.
+    ///
+    /// Longer messages are cut at the last character boundary within the
+    /// limit, followed by a marker such as `…[truncated 12345 bytes]`. The
+    /// limit applies to the message after all formatting stages ran, and
+    /// doesn't include the marker.
+    ///
+    /// Messages are written to a buffer holding at most `len` bytes, so
+    /// huge messages are never stored in full. With a [redactor], messages
+    /// are redacted in full first, so secrets crossing the limit are still
+    /// found.
+    ///
+    /// To limit the length of a single output's messages, see
+    /// [`Output::max_message_len`].
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// fern::Dispatch::new()
+    ///     .max_message_len(64 * 1024)
+    ///     .chain(std::io::stdout())
+    ///     # .into_log();
+    /// ```
+    ///
+    /// [`Output::max_message_len`]: struct.Output.html#method.max_message_len
+    /// [redactor]: #method.redact
+    #[inline]
+    pub fn max_message_len(mut self, len: usize) -> Self {
+        self.max_message_len = Some(len);
+        self
+    }
+
     /// Builds this dispatch and stores it in a clonable structure containing
     /// an [`Arc`].
     ///
@@ -417,6 +719,14 @@ impl Dispatch {
             default_level,
             levels,
             mut filters,
+            render_once,
+            multiline,
+            redactor,
+            max_message_len,
+            #[cfg(feature = "kv")]
+            fields,
+            #[cfg(feature = "kv")]
+            fields_from,
         } = self;
 
         let mut max_child_level = log::LevelFilter::Off;
@@ -438,6 +748,18 @@ impl Dispatch {
                         line_sep,
                     }))
                 }
+                OutputInner::StdSplit {
+                    threshold,
+                    line_sep,
+                } => {
+                    max_child_level = log::LevelFilter::Trace;
+                    Some(log_impl::Output::StdSplit(log_impl::StdSplit {
+                        stdout: io::stdout(),
+                        stderr: io::stderr(),
+                        threshold,
+                        line_sep,
+                    }))
+                }
                 OutputInner::File { stream, line_sep } => {
                     max_child_level = log::LevelFilter::Trace;
                     Some(log_impl::Output::File(log_impl::File {
@@ -476,64 +798,101 @@ impl Dispatch {
                     }))
                 }
                 #[cfg(all(not(windows), feature = "syslog-3"))]
-                OutputInner::Syslog3(log) => {
+                OutputInner::Syslog3 {
+                    logger,
+                    mut mapping,
+                } => {
                     max_child_level = log::LevelFilter::Trace;
-                    Some(log_impl::Output::Syslog3(log_impl::Syslog3 { inner: log }))
+                    // syslog 3 loggers don't expose their facility, so we can't
+                    // restore it for records which don't match any facility_for.
+                    if !mapping.facilities.is_empty() && mapping.default_facility.is_none() {
+                        mapping.default_facility = Some(SyslogFacility::User);
+                    }
+                    Some(log_impl::Output::Syslog3(log_impl::Syslog3 {
+                        inner: Mutex::new(logger),
+                        mapping: mapping.into_mapping(),
+                    }))
                 }
                 #[cfg(all(not(windows), feature = "syslog-4"))]
-                OutputInner::Syslog4Rfc3164(logger) => {
+                OutputInner::Syslog4Rfc3164 { logger, mapping } => {
                     max_child_level = log::LevelFilter::Trace;
                     Some(log_impl::Output::Syslog4Rfc3164(log_impl::Syslog4Rfc3164 {
                         inner: Mutex::new(logger),
+                        mapping: mapping.into_mapping(),
                     }))
                 }
                 #[cfg(all(not(windows), feature = "syslog-4"))]
-                OutputInner::Syslog4Rfc5424 { logger, transform } => {
+                OutputInner::Syslog4Rfc5424 {
+                    logger,
+                    transform,
+                    mapping,
+                } => {
                     max_child_level = log::LevelFilter::Trace;
                     Some(log_impl::Output::Syslog4Rfc5424(log_impl::Syslog4Rfc5424 {
                         inner: Mutex::new(logger),
                         transform,
+                        mapping: mapping.into_mapping(),
                     }))
                 }
                 #[cfg(all(not(windows), feature = "syslog-6"))]
-                OutputInner::Syslog6Rfc3164(logger) => {
+                OutputInner::Syslog6Rfc3164 { logger, mapping } => {
                     max_child_level = log::LevelFilter::Trace;
                     Some(log_impl::Output::Syslog6Rfc3164(log_impl::Syslog6Rfc3164 {
+                        default_facility: logger.formatter.facility,
                         inner: Mutex::new(logger),
+                        mapping: mapping.into_mapping(),
                     }))
                 }
                 #[cfg(all(not(windows), feature = "syslog-6"))]
-                OutputInner::Syslog6Rfc5424 { logger, transform } => {
+                OutputInner::Syslog6Rfc5424 {
+                    logger,
+                    transform,
+                    mapping,
+                } => {
                     max_child_level = log::LevelFilter::Trace;
                     Some(log_impl::Output::Syslog6Rfc5424(log_impl::Syslog6Rfc5424 {
+                        default_facility: logger.formatter.facility,
                         inner: Mutex::new(logger),
                         transform,
+                        mapping: mapping.into_mapping(),
                     }))
                 }
                 #[cfg(all(not(windows), feature = "syslog-7"))]
-                OutputInner::Syslog7Rfc3164(logger) => {
+                OutputInner::Syslog7Rfc3164 { logger, mapping } => {
                     max_child_level = log::LevelFilter::Trace;
                     Some(log_impl::Output::Syslog7Rfc3164(log_impl::Syslog7Rfc3164 {
+                        default_facility: logger.formatter.facility,
                         inner: Mutex::new(logger),
+                        mapping: mapping.into_mapping(),
                     }))
                 }
                 #[cfg(all(not(windows), feature = "syslog-7"))]
-                OutputInner::Syslog7Rfc5424 { logger, transform } => {
+                OutputInner::Syslog7Rfc5424 {
+                    logger,
+                    transform,
+                    mapping,
+                } => {
                     max_child_level = log::LevelFilter::Trace;
                     Some(log_impl::Output::Syslog7Rfc5424(log_impl::Syslog7Rfc5424 {
+                        default_facility: logger.formatter.facility,
                         inner: Mutex::new(logger),
                         transform,
+                        mapping: mapping.into_mapping(),
                     }))
                 }
+                OutputInner::MemoryBuffer(buffer) => {
+                    max_child_level = log::LevelFilter::Trace;
+                    Some(log_impl::Output::MemoryBuffer(buffer))
+                }
                 OutputInner::Panic => {
                     max_child_level = log::LevelFilter::Trace;
                     Some(log_impl::Output::Panic(log_impl::Panic))
                 }
-                OutputInner::Dispatch(child_dispatch) => {
+                OutputInner::Dispatch(child_dispatch) | OutputInner::Wrapped(child_dispatch) => {
                     let (child_level, child) = child_dispatch.into_dispatch();
                     if child_level > log::LevelFilter::Off {
                         max_child_level = cmp::max(max_child_level, child_level);
-                        Some(log_impl::Output::Dispatch(child))
+                        Some(log_impl::Output::Dispatch(Box::new(child)))
                     } else {
                         None
                     }
@@ -563,15 +922,22 @@ impl Dispatch {
                 OutputInner::DateBased { config } => {
                     max_child_level = log::LevelFilter::Trace;
 
+                    let timezone = if config.utc_time {
+                        log_impl::ConfiguredTimezone::Utc
+                    } else {
+                        log_impl::ConfiguredTimezone::Local
+                    };
+                    #[cfg(feature = "timezone")]
+                    let timezone = config
+                        .timezone
+                        .map_or(timezone, log_impl::ConfiguredTimezone::Zone);
+
                     let config = log_impl::DateBasedConfig::new(
                         config.line_sep,
                         config.file_prefix,
                         config.file_suffix,
-                        if config.utc_time {
-                            log_impl::ConfiguredTimezone::Utc
-                        } else {
-                            log_impl::ConfiguredTimezone::Local
-                        },
+                        timezone,
+                        config.clock,
                     );
 
                     let computed_suffix = config.compute_current_suffix();
@@ -584,6 +950,16 @@ impl Dispatch {
                         state: Mutex::new(DateBasedState::new(computed_suffix, initial_file)),
                     }))
                 }
+                #[cfg(all(target_os = "linux", feature = "journald"))]
+                OutputInner::Journald { config } => {
+                    max_child_level = log::LevelFilter::Trace;
+                    Some(log_impl::Output::Journald(log_impl::Journald {
+                        // ignore errors - we'll create a new socket when logging.
+                        socket: UnixDatagram::unbound().ok(),
+                        socket_path: config.socket_path,
+                        syslog_identifier: config.syslog_identifier,
+                    }))
+                }
             })
             .collect();
 
@@ -602,6 +978,12 @@ impl Dispatch {
             levels: levels.into(),
             format,
             filters,
+            render_once,
+            multiline,
+            redactor: redactor.map(Box::new),
+            max_message_len,
+            #[cfg(feature = "kv")]
+            fields: log_impl::Fields::new(fields, fields_from),
         };
 
         (real_min, dispatch)
@@ -656,6 +1038,116 @@ impl Dispatch {
 
         Ok(())
     }
+
+    /// Builds this logger and instantiates it as the global [`log`] logger,
+    /// returning a handle which can later flush and close it.
+    ///
+    /// Loggers set with [`Dispatch::apply`] are never dropped, so buffered
+    /// output can be lost when the process exits through
+    /// [`std::process::exit`]. Calling [`ShutdownHandle::shutdown`] first
+    /// prevents this.
+    ///
+    /// Example usage:
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let handle = fern::Dispatch::new()
+    ///     .chain(fern::log_file("program.log")?)
+    ///     .apply_with_shutdown()?;
+    ///
+    /// log::info!("exiting");
+    ///
+    /// handle.shutdown();
+    /// std::process::exit(0);
+    /// # }
+    /// ```
+    ///
+    /// # Errors:
+    ///
+    /// This function will return an error if a global logger has already been
+    /// set to a previous logger.
+    ///
+    /// [`log`]: https://github.com/rust-lang-nursery/log
+    /// [`Dispatch::apply`]: #method.apply
+    /// [`ShutdownHandle::shutdown`]: struct.ShutdownHandle.html#method.shutdown
+    /// [`std::process::exit`]: https://doc.rust-lang.org/std/process/fn.exit.html
+    pub fn apply_with_shutdown(self) -> Result<ShutdownHandle, log::SetLoggerError> {
+        let (max_level, dispatch) = self.into_dispatch();
+
+        let logger: &'static log_impl::Closeable = Box::leak(Box::new(log_impl::Closeable {
+            closed: AtomicBool::new(false),
+            to_stderr: AtomicBool::new(false),
+            dispatch: RwLock::new(Some(Arc::new(dispatch))),
+        }));
+        log::set_logger(logger)?;
+        log::set_max_level(max_level);
+
+        Ok(ShutdownHandle { logger })
+    }
+}
+
+/// Handle to a global logger set with [`Dispatch::apply_with_shutdown`].
+///
+/// [`Dispatch::apply_with_shutdown`]: struct.Dispatch.html#method.apply_with_shutdown
+#[derive(Clone, Copy)]
+pub struct ShutdownHandle {
+    logger: &'static log_impl::Closeable,
+}
+
+impl ShutdownHandle {
+    /// Flushes all outputs, including those of nested dispatches.
+    pub fn flush(&self) {
+        self.logger.flush();
+    }
+
+    /// Flushes and closes all outputs, and discards any further records.
+    ///
+    /// This waits for records currently being logged on other threads to
+    /// finish, but records they log while doing so, such as from a
+    /// formatter, are already discarded. When called while logging a record
+    /// on this thread, the outputs are closed once that record is done.
+    /// Outputs in a [`SharedDispatch`] are flushed, but only closed once no
+    /// other logger uses them.
+    ///
+    /// Calling this more than once, or after [`shutdown_to_stderr`], has no
+    /// further effect: the logger keeps handling records the way the first
+    /// call chose.
+    ///
+    /// [`SharedDispatch`]: struct.SharedDispatch.html
+    /// [`shutdown_to_stderr`]: #method.shutdown_to_stderr
+    pub fn shutdown(&self) {
+        if self.logger.close(false) {
+            log::set_max_level(log::LevelFilter::Off);
+        }
+    }
+
+    /// Flushes and closes all outputs, like [`shutdown`], but prints any
+    /// further records' messages to stderr instead of discarding them.
+    ///
+    /// The global maximum level is left as it was, so records more verbose
+    /// than the logger allowed before still aren't printed.
+    ///
+    /// Calling this after [`shutdown`] has no effect, and further records are
+    /// still discarded. Likewise, calling [`shutdown`] after this keeps
+    /// printing them.
+    ///
+    /// [`shutdown`]: #method.shutdown
+    pub fn shutdown_to_stderr(&self) {
+        self.logger.close(true);
+    }
+
+    /// Returns `true` if the logger has been shut down.
+    pub fn is_shutdown(&self) -> bool {
+        self.logger.closed.load(Ordering::SeqCst)
+    }
+}
+
+impl fmt::Debug for ShutdownHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ShutdownHandle")
+            .field("shutdown", &self.is_shutdown())
+            .finish()
+    }
 }
 
 /// This enum contains various outputs that you can send messages to.
@@ -670,6 +1162,12 @@ enum OutputInner {
         stream: io::Stderr,
         line_sep: Cow<'static, str>,
     },
+    /// Prints messages at or above `threshold` to stderr, and others to
+    /// stdout, with `line_sep` separator.
+    StdSplit {
+        threshold: log::Level,
+        line_sep: Cow<'static, str>,
+    },
     /// Writes all messages to file with `line_sep` separator.
     File {
         stream: fs::File,
@@ -701,6 +1199,10 @@ enum OutputInner {
     },
     /// Passes all messages to other dispatch.
     Dispatch(Dispatch),
+    /// Passes all messages to a dispatch wrapping a single output, created by
+    /// `Output::formatted`, `Output::multiline`, `Output::max_message_len`
+    /// and `Output::strip_ansi`.
+    Wrapped(Dispatch),
     /// Passes all messages to other dispatch that's shared.
     SharedDispatch(SharedDispatch),
     /// Passes all messages to other logger.
@@ -709,37 +1211,57 @@ enum OutputInner {
     OtherStatic(&'static dyn Log),
     /// Passes all messages to the syslog.
     #[cfg(all(not(windows), feature = "syslog-3"))]
-    Syslog3(syslog3::Logger),
+    Syslog3 {
+        logger: syslog3::Logger,
+        mapping: SyslogMapping,
+    },
     /// Passes all messages to the syslog.
     #[cfg(all(not(windows), feature = "syslog-4"))]
-    Syslog4Rfc3164(Syslog4Rfc3164Logger),
+    Syslog4Rfc3164 {
+        logger: Syslog4Rfc3164Logger,
+        mapping: SyslogMapping,
+    },
     /// Sends all messages through the transform then passes to the syslog.
     #[cfg(all(not(windows), feature = "syslog-4"))]
     Syslog4Rfc5424 {
         logger: Syslog4Rfc5424Logger,
         transform: Box<Syslog4TransformFn>,
+        mapping: SyslogMapping,
     },
     #[cfg(all(not(windows), feature = "syslog-6"))]
-    Syslog6Rfc3164(Syslog6Rfc3164Logger),
+    Syslog6Rfc3164 {
+        logger: Syslog6Rfc3164Logger,
+        mapping: SyslogMapping,
+    },
     /// Sends all messages through the transform then passes to the syslog.
     #[cfg(all(not(windows), feature = "syslog-6"))]
     Syslog6Rfc5424 {
         logger: Syslog6Rfc5424Logger,
         transform: Box<Syslog6TransformFn>,
+        mapping: SyslogMapping,
     },
     #[cfg(all(not(windows), feature = "syslog-7"))]
-    Syslog7Rfc3164(Syslog7Rfc3164Logger),
+    Syslog7Rfc3164 {
+        logger: Syslog7Rfc3164Logger,
+        mapping: SyslogMapping,
+    },
     /// Sends all messages through the transform then passes to the syslog.
     #[cfg(all(not(windows), feature = "syslog-7"))]
     Syslog7Rfc5424 {
         logger: Syslog7Rfc5424Logger,
         transform: Box<Syslog7TransformFn>,
+        mapping: SyslogMapping,
     },
+    /// Stores the last few messages in memory.
+    MemoryBuffer(MemoryBuffer),
     /// Panics with messages text for all messages.
     Panic,
     /// File logger with custom date and timestamp suffix in file name.
     #[cfg(feature = "date-based")]
     DateBased { config: DateBased },
+    /// Sends all messages to the systemd journal.
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    Journald { config: Journald },
 }
 
 /// Logger which will panic whenever anything is logged. The panic
@@ -910,10 +1432,14 @@ impl From<syslog3::Logger> for Output {
     ///
     /// Log levels are translated trace => debug, debug => debug, info =>
     /// informational, warn => warning, and error => error.
+    /// Use [`Output::syslog_mapped`] to customize this, or the facility.
     ///
     /// This requires the `"syslog-3"` feature.
     fn from(log: syslog3::Logger) -> Self {
-        Output(OutputInner::Syslog3(log))
+        Output(OutputInner::Syslog3 {
+            logger: log,
+            mapping: SyslogMapping::new(),
+        })
     }
 }
 
@@ -924,6 +1450,7 @@ impl From<Box<syslog3::Logger>> for Output {
     ///
     /// Log levels are translated trace => debug, debug => debug, info =>
     /// informational, warn => warning, and error => error.
+    /// Use [`Output::syslog_mapped`] to customize this, or the facility.
     ///
     /// Note that while this takes a `Box<Logger>` for convenience (syslog
     /// methods return `Box`es), it will be immediately unboxed upon storage
@@ -932,7 +1459,10 @@ impl From<Box<syslog3::Logger>> for Output {
     ///
     /// This requires the `"syslog-3"` feature.
     fn from(log: Box<syslog3::Logger>) -> Self {
-        Output(OutputInner::Syslog3(*log))
+        Output(OutputInner::Syslog3 {
+            logger: *log,
+            mapping: SyslogMapping::new(),
+        })
     }
 }
 
@@ -942,6 +1472,7 @@ impl From<Syslog4Rfc3164Logger> for Output {
     ///
     /// Log levels are translated trace => debug, debug => debug, info =>
     /// informational, warn => warning, and error => error.
+    /// Use [`Output::syslog_mapped`] to customize this, or the facility.
     ///
     /// Note that due to <https://github.com/Geal/rust-syslog/issues/41>,
     /// logging to this backend requires one allocation per log call.
@@ -951,7 +1482,10 @@ impl From<Syslog4Rfc3164Logger> for Output {
     ///
     /// This requires the `"syslog-4"` feature.
     fn from(log: Syslog4Rfc3164Logger) -> Self {
-        Output(OutputInner::Syslog4Rfc3164(log))
+        Output(OutputInner::Syslog4Rfc3164 {
+            logger: log,
+            mapping: SyslogMapping::new(),
+        })
     }
 }
 
@@ -961,6 +1495,7 @@ impl From<Syslog6Rfc3164Logger> for Output {
     ///
     /// Log levels are translated trace => debug, debug => debug, info =>
     /// informational, warn => warning, and error => error.
+    /// Use [`Output::syslog_mapped`] to customize this, or the facility.
     ///
     /// Note that due to <https://github.com/Geal/rust-syslog/issues/41>,
     /// logging to this backend requires one allocation per log call.
@@ -970,7 +1505,10 @@ impl From<Syslog6Rfc3164Logger> for Output {
     ///
     /// This requires the `"syslog-6"` feature.
     fn from(log: Syslog6Rfc3164Logger) -> Self {
-        Output(OutputInner::Syslog6Rfc3164(log))
+        Output(OutputInner::Syslog6Rfc3164 {
+            logger: log,
+            mapping: SyslogMapping::new(),
+        })
     }
 }
 
@@ -980,6 +1518,7 @@ impl From<Syslog7Rfc3164Logger> for Output {
     ///
     /// Log levels are translated trace => debug, debug => debug, info =>
     /// informational, warn => warning, and error => error.
+    /// Use [`Output::syslog_mapped`] to customize this, or the facility.
     ///
     /// Note that due to <https://github.com/Geal/rust-syslog/issues/41>,
     /// logging to this backend requires one allocation per log call.
@@ -989,7 +1528,18 @@ impl From<Syslog7Rfc3164Logger> for Output {
     ///
     /// This requires the `"syslog-7"` feature.
     fn from(log: Syslog7Rfc3164Logger) -> Self {
-        Output(OutputInner::Syslog7Rfc3164(log))
+        Output(OutputInner::Syslog7Rfc3164 {
+            logger: log,
+            mapping: SyslogMapping::new(),
+        })
+    }
+}
+
+impl From<MemoryBuffer> for Output {
+    /// Creates an output logger storing the last few records in the given
+    /// buffer.
+    fn from(buffer: MemoryBuffer) -> Self {
+        Output(OutputInner::MemoryBuffer(buffer))
     }
 }
 
@@ -1218,46 +1768,267 @@ impl Output {
         })
     }
 
-    /// Returns a mpsc::Sender logger using a custom separator.
+    /// Returns an output which formats records before passing them to the
+    /// given output.
     ///
-    /// If the default separator of `\n` is acceptable, an
-    /// `mpsc::Sender<String>` instance can be passed into `Dispatch::
-    /// chain()` directly.
+    /// The formatter works in the same way as one set with
+    /// [`Dispatch::format`], but only applies to this output. This allows a
+    /// single dispatch to write different formats to different outputs, or
+    /// to add line separators to outputs whose constructors don't take one.
     ///
-    /// Each log message will be suffixed with the separator, then sent as a
-    /// single String to the given sender.
+    /// Example usage:
     ///
-    /// ```
-    /// use std::sync::mpsc::channel;
+    /// This writes JSON lines to a file, and colored text to stdout.
     ///
-    /// let (tx, rx) = channel();
-    /// fern::Dispatch::new().chain(tx)
-    ///     # .into_log();
+    /// ```no_run
+    /// /// Quotes and escapes text as a JSON string.
+    /// fn json_string(text: &str) -> String {
+    ///     let mut escaped = String::with_capacity(text.len() + 2);
+    ///     escaped.push('"');
+    ///     for c in text.chars() {
+    ///         match c {
+    ///             '"' => escaped.push_str("\\\""),
+    ///             '\\' => escaped.push_str("\\\\"),
+    ///             '\n' => escaped.push_str("\\n"),
+    ///             '\r' => escaped.push_str("\\r"),
+    ///             '\t' => escaped.push_str("\\t"),
+    ///             c if c < ' ' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+    ///             c => escaped.push(c),
+    ///         }
+    ///     }
+    ///     escaped.push('"');
+    ///     escaped
+    /// }
+    ///
+    /// # fn setup_logger() -> Result<(), fern::InitError> {
+    /// fern::Dispatch::new()
+    ///     .level(log::LevelFilter::Info)
+    ///     .chain(fern::Output::formatted(
+    ///         fern::log_file("output.jsonl")?,
+    ///         |out, message, record| {
+    ///             out.finish(format_args!(
+    ///                 r#"{{"level":"{}","target":{},"message":{}}}"#,
+    ///                 record.level(),
+    ///                 json_string(record.target()),
+    ///                 json_string(&message.to_string())
+    ///             ))
+    ///         },
+    ///     ))
+    ///     .chain(fern::Output::formatted(std::io::stdout(), |out, message, record| {
+    ///         out.finish(format_args!("\x1B[1m{}\x1B[0m {}", record.level(), message))
+    ///     }))
+    ///     .apply()?;
+    /// # Ok(())
+    /// # }
     /// ```
-    pub fn sender<T: Into<Cow<'static, str>>>(sender: Sender<String>, line_sep: T) -> Self {
-        Output(OutputInner::Sender {
-            stream: sender,
-            line_sep: line_sep.into(),
-        })
+    ///
+    /// [`Dispatch::format`]: struct.Dispatch.html#method.format
+    pub fn formatted<T, F>(output: T, formatter: F) -> Self
+    where
+        T: Into<Output>,
+        F: Fn(FormatCallback, &fmt::Arguments, &log::Record) + Sync + Send + 'static,
+    {
+        Output(OutputInner::Wrapped(
+            Dispatch::new().format(formatter).chain(output),
+        ))
     }
 
-    /// Returns a logger which logs into an RFC5424 syslog.
+    /// Returns an output which applies a multiline policy to records before
+    /// passing them to the given output.
     ///
-    /// This method takes an additional transform method to turn the log data
-    /// into RFC5424 data.
+    /// This works the same way as [`Dispatch::multiline`], but only applies
+    /// to this output. Since the parent dispatch has already formatted the
+    /// message, [`Multiline::RepeatHeader`] only repeats a header when
+    /// wrapping an output with its own formatter, such as one created by
+    /// [`Output::formatted`].
     ///
-    /// I've honestly got no clue what the expected keys and values are for
-    /// this kind of logging, so I'm just going to link [the rfc] instead.
+    /// Example usage:
     ///
-    /// If you're an expert on syslog logging and would like to contribute
-    /// an example to put here, it would be gladly accepted!
+    /// ```no_run
+    /// # fn setup_logger() -> Result<(), fern::InitError> {
+    /// use fern::{Multiline, Output};
     ///
-    /// This requires the `"syslog-4"` feature.
+    /// let format = |out: fern::FormatCallback, message: &std::fmt::Arguments, record: &log::Record| {
+    ///     out.finish(format_args!("[{}] {}", record.level(), message))
+    /// };
     ///
-    /// [the rfc]: https://tools.ietf.org/html/rfc5424
-    #[cfg(all(not(windows), feature = "syslog-4"))]
-    pub fn syslog_5424<F>(logger: Syslog4Rfc5424Logger, transform: F) -> Self
-    where
+    /// fern::Dispatch::new()
+    ///     // "[ERROR] first line\n    second line\n"
+    ///     .chain(Output::multiline(
+    ///         Output::formatted(std::io::stdout(), format),
+    ///         Multiline::Indent("    ".into()),
+    ///     ))
+    ///     // "[ERROR] first line\\nsecond line\n"
+    ///     .chain(Output::multiline(
+    ///         Output::formatted(fern::log_file("escaped.log")?, format),
+    ///         Multiline::Escape,
+    ///     ))
+    ///     // "[ERROR] first line\n[ERROR] second line\n"
+    ///     .chain(Output::multiline(
+    ///         Output::formatted(fern::log_file("repeated.log")?, format),
+    ///         Multiline::RepeatHeader,
+    ///     ))
+    ///     .apply()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Dispatch::multiline`]: struct.Dispatch.html#method.multiline
+    /// [`Multiline::RepeatHeader`]: enum.Multiline.html#variant.RepeatHeader
+    /// [`Output::formatted`]: #method.formatted
+    pub fn multiline<T: Into<Output>>(output: T, policy: Multiline) -> Self {
+        Output(OutputInner::Wrapped(
+            Dispatch::new().multiline(policy).chain(output),
+        ))
+    }
+
+    /// Returns an output which truncates messages longer than `len` bytes
+    /// before passing them to the given output.
+    ///
+    /// This works the same way as [`Dispatch::max_message_len`], but only
+    /// applies to this output.
+    ///
+    /// Example usage:
+    ///
+    /// ```no_run
+    /// # fn setup_logger() -> Result<(), fern::InitError> {
+    /// fern::Dispatch::new()
+    ///     .chain(std::io::stdout())
+    ///     // the log shipper rejects lines over 16 KiB
+    ///     .chain(fern::Output::max_message_len(
+    ///         fern::log_file("shipped.log")?,
+    ///         16 * 1024,
+    ///     ))
+    ///     .apply()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Dispatch::max_message_len`]: struct.Dispatch.html#method.max_message_len
+    pub fn max_message_len<T: Into<Output>>(output: T, len: usize) -> Self {
+        Output(OutputInner::Wrapped(
+            Dispatch::new().max_message_len(len).chain(output),
+        ))
+    }
+
+    /// Returns an output which removes ANSI escape sequences, such as
+    /// colors, from messages before passing them to the given output.
+    ///
+    /// Sequences are removed while the message is written, without storing
+    /// it in full.
+    ///
+    /// Colors from the [`colors`] module are already left out of outputs
+    /// which don't support them. This is useful for messages colored in
+    /// other ways, such as by the [`colored`] crate or by the program
+    /// logging them.
+    ///
+    /// Example usage:
+    ///
+    /// ```no_run
+    /// # fn setup_logger() -> Result<(), fern::InitError> {
+    /// fern::Dispatch::new()
+    ///     .format(|out, message, record| {
+    ///         out.finish(format_args!("\x1B[1m{}\x1B[0m {}", record.level(), message))
+    ///     })
+    ///     .chain(std::io::stdout())
+    ///     .chain(fern::Output::strip_ansi(fern::log_file("output.log")?))
+    ///     .apply()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`colors`]: colors/index.html
+    /// [`colored`]: https://docs.rs/colored/
+    pub fn strip_ansi<T: Into<Output>>(output: T) -> Self {
+        Output::formatted(output, |out, message, _| {
+            out.finish(format_args!("{}", log_impl::StripAnsi(message)))
+        })
+    }
+
+    /// Returns a logger which prints records at or above the given level to
+    /// stderr, and all others to stdout, using `\n` as the separator.
+    ///
+    /// This replaces the common setup of two child dispatches filtering
+    /// records into stdout and stderr. Both streams are written to while
+    /// holding the stdout lock, and stdout is flushed after each record, so
+    /// records from different threads won't interleave mid-line, and records
+    /// appear in the order they were logged.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// fern::Dispatch::new()
+    ///     .level(log::LevelFilter::Info)
+    ///     // warnings and errors go to stderr, everything else to stdout
+    ///     .chain(fern::Output::std_split(log::Level::Warn))
+    ///     # .into_log();
+    /// ```
+    pub fn std_split(threshold: log::Level) -> Self {
+        Output::std_split_with_line_sep(threshold, "\n")
+    }
+
+    /// Returns a logger which prints records at or above the given level to
+    /// stderr, and all others to stdout, using a custom separator.
+    ///
+    /// See [`Output::std_split`] for details.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// fern::Dispatch::new()
+    ///     .chain(fern::Output::std_split_with_line_sep(log::Level::Warn, "\r\n"))
+    ///     # .into_log();
+    /// ```
+    pub fn std_split_with_line_sep<T: Into<Cow<'static, str>>>(
+        threshold: log::Level,
+        line_sep: T,
+    ) -> Self {
+        Output(OutputInner::StdSplit {
+            threshold,
+            line_sep: line_sep.into(),
+        })
+    }
+
+    /// Returns a mpsc::Sender logger using a custom separator.
+    ///
+    /// If the default separator of `\n` is acceptable, an
+    /// `mpsc::Sender<String>` instance can be passed into `Dispatch::
+    /// chain()` directly.
+    ///
+    /// Each log message will be suffixed with the separator, then sent as a
+    /// single String to the given sender.
+    ///
+    /// ```
+    /// use std::sync::mpsc::channel;
+    ///
+    /// let (tx, rx) = channel();
+    /// fern::Dispatch::new().chain(tx)
+    ///     # .into_log();
+    /// ```
+    pub fn sender<T: Into<Cow<'static, str>>>(sender: Sender<String>, line_sep: T) -> Self {
+        Output(OutputInner::Sender {
+            stream: sender,
+            line_sep: line_sep.into(),
+        })
+    }
+
+    /// Returns a logger which logs into an RFC5424 syslog.
+    ///
+    /// This method takes an additional transform method to turn the log data
+    /// into RFC5424 data.
+    ///
+    /// I've honestly got no clue what the expected keys and values are for
+    /// this kind of logging, so I'm just going to link [the rfc] instead.
+    ///
+    /// If you're an expert on syslog logging and would like to contribute
+    /// an example to put here, it would be gladly accepted!
+    ///
+    /// This requires the `"syslog-4"` feature.
+    ///
+    /// [the rfc]: https://tools.ietf.org/html/rfc5424
+    #[cfg(all(not(windows), feature = "syslog-4"))]
+    pub fn syslog_5424<F>(logger: Syslog4Rfc5424Logger, transform: F) -> Self
+    where
         F: Fn(&log::Record) -> (i32, HashMap<String, HashMap<String, String>>, String)
             + Sync
             + Send
@@ -1266,6 +2037,7 @@ impl Output {
         Output(OutputInner::Syslog4Rfc5424 {
             logger,
             transform: Box::new(transform),
+            mapping: SyslogMapping::new(),
         })
     }
 
@@ -1294,6 +2066,7 @@ impl Output {
         Output(OutputInner::Syslog6Rfc5424 {
             logger,
             transform: Box::new(transform),
+            mapping: SyslogMapping::new(),
         })
     }
 
@@ -1322,9 +2095,96 @@ impl Output {
         Output(OutputInner::Syslog7Rfc5424 {
             logger,
             transform: Box::new(transform),
+            mapping: SyslogMapping::new(),
         })
     }
 
+    /// Returns the given syslog logger, customized to use the given severity
+    /// and facility mapping.
+    ///
+    /// `output` may be any syslog logger fern supports, or the result of one
+    /// of the `Output::syslog*_5424` methods.
+    ///
+    /// See [`SyslogMapping`] for details.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `output` is not a syslog logger.
+    ///
+    /// Example usage:
+    ///
+    /// ```no_run
+    /// # use syslog7 as syslog;
+    /// # fn setup_logging() -> Result<(), Box<dyn std::error::Error>> {
+    /// use fern::{SyslogFacility, SyslogMapping, SyslogSeverity};
+    ///
+    /// let formatter = syslog::Formatter3164 {
+    ///     facility: syslog::Facility::LOG_USER,
+    ///     hostname: None,
+    ///     process: "hello-world".to_owned(),
+    ///     pid: 0,
+    /// };
+    ///
+    /// let mapping = SyslogMapping::new()
+    ///     .facility_for("my_crate::auth", SyslogFacility::Auth)
+    ///     .severity(|record| match (record.level(), record.target()) {
+    ///         (log::Level::Info, "my_crate::lifecycle") => SyslogSeverity::Notice,
+    ///         (log::Level::Error, "my_crate::db") => SyslogSeverity::Critical,
+    ///         (level, _) => level.into(),
+    ///     });
+    ///
+    /// fern::Dispatch::new()
+    ///     .chain(fern::Output::syslog_mapped(syslog::unix(formatter)?, mapping))
+    ///     .apply()?;
+    /// # Ok(())
+    /// # }
+    /// # fn main() { setup_logging().ok(); }
+    /// ```
+    #[cfg(all(
+        not(windows),
+        any(
+            feature = "syslog-3",
+            feature = "syslog-4",
+            feature = "syslog-6",
+            feature = "syslog-7"
+        )
+    ))]
+    pub fn syslog_mapped<T: Into<Output>>(output: T, mapping: SyslogMapping) -> Self {
+        let mut output = output.into();
+        match output.0 {
+            #[cfg(all(not(windows), feature = "syslog-3"))]
+            OutputInner::Syslog3 {
+                mapping: ref mut m, ..
+            } => *m = mapping,
+            #[cfg(all(not(windows), feature = "syslog-4"))]
+            OutputInner::Syslog4Rfc3164 {
+                mapping: ref mut m, ..
+            } => *m = mapping,
+            #[cfg(all(not(windows), feature = "syslog-4"))]
+            OutputInner::Syslog4Rfc5424 {
+                mapping: ref mut m, ..
+            } => *m = mapping,
+            #[cfg(all(not(windows), feature = "syslog-6"))]
+            OutputInner::Syslog6Rfc3164 {
+                mapping: ref mut m, ..
+            } => *m = mapping,
+            #[cfg(all(not(windows), feature = "syslog-6"))]
+            OutputInner::Syslog6Rfc5424 {
+                mapping: ref mut m, ..
+            } => *m = mapping,
+            #[cfg(all(not(windows), feature = "syslog-7"))]
+            OutputInner::Syslog7Rfc3164 {
+                mapping: ref mut m, ..
+            } => *m = mapping,
+            #[cfg(all(not(windows), feature = "syslog-7"))]
+            OutputInner::Syslog7Rfc5424 {
+                mapping: ref mut m, ..
+            } => *m = mapping,
+            _ => panic!("fern::Output::syslog_mapped: output is not a syslog logger"),
+        }
+        output
+    }
+
     /// Returns a logger which simply calls the given function with each
     /// message.
     ///
@@ -1382,6 +2242,14 @@ impl fmt::Debug for Dispatch {
                     .finish()
             }
         }
+        struct FormatDebug<'a>(&'a [Box<Formatter>]);
+        impl fmt::Debug for FormatDebug<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_list()
+                    .entries(self.0.iter().map(|_| "<formatter closure>"))
+                    .finish()
+            }
+        }
         struct FiltersDebug<'a>(&'a [Box<Filter>]);
         impl fmt::Debug for FiltersDebug<'_> {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -1390,16 +2258,32 @@ impl fmt::Debug for Dispatch {
                     .finish()
             }
         }
-        f.debug_struct("Dispatch")
-            .field(
-                "format",
-                &self.format.as_ref().map(|_| "<formatter closure>"),
-            )
+        #[cfg(feature = "kv")]
+        struct FieldsFromDebug<'a>(&'a [Box<FieldsFn>]);
+        #[cfg(feature = "kv")]
+        impl fmt::Debug for FieldsFromDebug<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_list()
+                    .entries(self.0.iter().map(|_| "<fields closure>"))
+                    .finish()
+            }
+        }
+        let mut debug = f.debug_struct("Dispatch");
+        debug
+            .field("format", &FormatDebug(&self.format))
             .field("children", &self.children)
             .field("default_level", &self.default_level)
             .field("levels", &LevelsDebug(&self.levels))
             .field("filters", &FiltersDebug(&self.filters))
-            .finish()
+            .field("render_once", &self.render_once)
+            .field("multiline", &self.multiline)
+            .field("redactor", &self.redactor)
+            .field("max_message_len", &self.max_message_len);
+        #[cfg(feature = "kv")]
+        debug
+            .field("fields", &self.fields)
+            .field("fields_from", &FieldsFromDebug(&self.fields_from));
+        debug.finish()
     }
 }
 
@@ -1422,6 +2306,14 @@ impl fmt::Debug for OutputInner {
                 .field("stream", stream)
                 .field("line_sep", line_sep)
                 .finish(),
+            OutputInner::StdSplit {
+                ref threshold,
+                ref line_sep,
+            } => f
+                .debug_struct("Output::StdSplit")
+                .field("threshold", threshold)
+                .field("line_sep", line_sep)
+                .finish(),
             OutputInner::File {
                 ref stream,
                 ref line_sep,
@@ -1459,7 +2351,7 @@ impl fmt::Debug for OutputInner {
                 .field("line_sep", line_sep)
                 .finish(),
             #[cfg(all(not(windows), feature = "syslog-3"))]
-            OutputInner::Syslog3(_) => f
+            OutputInner::Syslog3 { .. } => f
                 .debug_tuple("Output::Syslog3")
                 .field(&"<unprintable syslog::Logger>")
                 .finish(),
@@ -1496,6 +2388,9 @@ impl fmt::Debug for OutputInner {
             OutputInner::Dispatch(ref dispatch) => {
                 f.debug_tuple("Output::Dispatch").field(dispatch).finish()
             }
+            OutputInner::Wrapped(ref dispatch) => {
+                f.debug_tuple("Output::Wrapped").field(dispatch).finish()
+            }
             OutputInner::SharedDispatch(_) => f
                 .debug_tuple("Output::SharedDispatch")
                 .field(&"<built Dispatch logger>")
@@ -1508,12 +2403,20 @@ impl fmt::Debug for OutputInner {
                 .debug_tuple("Output::OtherStatic")
                 .field(&"<boxed logger>")
                 .finish(),
+            OutputInner::MemoryBuffer(ref buffer) => {
+                f.debug_tuple("Output::MemoryBuffer").field(buffer).finish()
+            }
             OutputInner::Panic => f.debug_tuple("Output::Panic").finish(),
             #[cfg(feature = "date-based")]
             OutputInner::DateBased { ref config } => f
                 .debug_struct("Output::DateBased")
                 .field("config", config)
                 .finish(),
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            OutputInner::Journald { ref config } => f
+                .debug_struct("Output::Journald")
+                .field("config", config)
+                .finish(),
         }
     }
 }
@@ -1534,6 +2437,9 @@ pub struct DateBased {
     file_suffix: Cow<'static, str>,
     line_sep: Cow<'static, str>,
     utc_time: bool,
+    #[cfg(feature = "timezone")]
+    timezone: Option<crate::time::Timezone>,
+    clock: Arc<dyn crate::time::Clock>,
 }
 
 #[cfg(feature = "date-based")]
@@ -1557,7 +2463,9 @@ impl DateBased {
     /// "%Y")`, the result will be a filepath `hello2019`.
     ///
     /// By default, this will use local time. For UTC time instead, use the
-    /// [`.utc_time()`][DateBased::utc_time] method after creating.
+    /// [`.utc_time()`][DateBased::utc_time] method after creating. With the
+    /// `timezone` feature, `.timezone()` also accepts fixed offsets and POSIX
+    /// time zone rules.
     ///
     /// By default, this will use `\n` as a line separator. For a custom
     /// separator, use the [`.line_sep`][DateBased::line_sep] method
@@ -1626,6 +2534,9 @@ impl DateBased {
             file_prefix: file_prefix.as_ref().to_owned(),
             file_suffix: file_suffix.into(),
             line_sep: "\n".into(),
+            #[cfg(feature = "timezone")]
+            timezone: None,
+            clock: Arc::new(crate::time::SystemClock),
         }
     }
 
@@ -1662,6 +2573,10 @@ impl DateBased {
     /// ```
     pub fn utc_time(mut self) -> Self {
         self.utc_time = true;
+        #[cfg(feature = "timezone")]
+        {
+            self.timezone = None;
+        }
         self
     }
 
@@ -1682,6 +2597,67 @@ impl DateBased {
     /// ```
     pub fn local_time(mut self) -> Self {
         self.utc_time = false;
+        #[cfg(feature = "timezone")]
+        {
+            self.timezone = None;
+        }
+        self
+    }
+
+    /// Orients this log file suffix formatting to use the given time zone,
+    /// such as a fixed offset from UTC.
+    ///
+    /// `%z` in the suffix is written as the offset in effect, such as
+    /// `+0530`.
+    ///
+    /// Requires the `timezone` feature.
+    ///
+    /// # Examples
+    ///
+    /// This will use India Standard Time to determine the date:
+    ///
+    /// ```
+    /// use fern::time::UtcOffset;
+    ///
+    /// // program.log.2310201905
+    /// let log = fern::DateBased::new("my-program.log.", "%d%m%Y%H")
+    ///     .timezone("+05:30".parse::<UtcOffset>().unwrap());
+    /// ```
+    ///
+    /// And this will use the same local time as
+    /// [`Timezone::local`][crate::time::Timezone::local] timestamps:
+    ///
+    /// ```
+    /// use fern::time::Timezone;
+    ///
+    /// let log = fern::DateBased::new("my-program.log.", "%d%m%Y%H").timezone(Timezone::local());
+    /// ```
+    #[cfg(feature = "timezone")]
+    pub fn timezone<T: Into<crate::time::Timezone>>(mut self, timezone: T) -> Self {
+        self.timezone = Some(timezone.into());
+        self
+    }
+
+    /// Sets the clock used to decide which file records go to.
+    ///
+    /// The default is the system clock. This is mostly useful for testing
+    /// rotation without waiting for the time to change.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use fern::time::ManualClock;
+    ///
+    /// let clock = ManualClock::new(SystemTime::now());
+    /// let log = fern::DateBased::new("my-program.log.", "%d%m%Y%H").clock(clock.clone());
+    ///
+    /// // later, move to the next file.
+    /// clock.advance(Duration::from_secs(3600));
+    /// ```
+    pub fn clock<C: crate::time::Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
         self
     }
 }
@@ -1694,3 +2670,379 @@ impl From<DateBased> for Output {
         Output(OutputInner::DateBased { config })
     }
 }
+
+/// Logger which sends records to the systemd journal using its native
+/// protocol.
+///
+/// Each record is sent with these fields:
+///
+/// - `MESSAGE`: the formatted message
+/// - `PRIORITY`: the record's level, translated as for syslog (see
+///   [`SyslogSeverity`])
+/// - `TARGET`: the record's target
+/// - `CODE_FILE`, `CODE_LINE` and `CODE_MODULE`, when known
+/// - `THREAD`: the name of the thread logging, or its id if unnamed
+/// - `SYSLOG_IDENTIFIER`, if set with [`Journald::syslog_identifier`]
+///
+/// along with each of the record's key-values. Keys are converted into valid
+/// journal field names by uppercasing them and replacing any other
+/// characters with `_`. Keys which would overwrite one of the fields above,
+/// such as `message`, are prefixed with `KV_`, and keys starting with `_` are
+/// skipped, as journald reserves those for fields it adds itself.
+///
+/// Records too large to send as a single datagram are passed to journald
+/// in a sealed memfd instead.
+///
+/// This is only available on Linux, and requires the `journald` feature.
+///
+/// Example usage:
+///
+/// ```no_run
+/// # fn setup_logger() -> Result<(), fern::InitError> {
+/// fern::Dispatch::new()
+///     .level(log::LevelFilter::Info)
+///     .chain(fern::Journald::new().syslog_identifier("my-program"))
+///     .apply()?;
+/// # Ok(())
+/// # }
+/// #
+/// # fn main() { setup_logger().expect("failed to set up logger"); }
+/// ```
+#[derive(Debug)]
+#[cfg(all(target_os = "linux", feature = "journald"))]
+pub struct Journald {
+    socket_path: PathBuf,
+    syslog_identifier: Option<Cow<'static, str>>,
+}
+
+#[cfg(all(target_os = "linux", feature = "journald"))]
+impl Journald {
+    /// Creates a journal logger sending to `/run/systemd/journal/socket`.
+    pub fn new() -> Self {
+        Journald {
+            socket_path: "/run/systemd/journal/socket".into(),
+            syslog_identifier: None,
+        }
+    }
+
+    /// Changes the socket this logger sends records to.
+    ///
+    /// This is mainly useful for testing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let log = fern::Journald::new().socket_path("/tmp/test-journal.sock");
+    /// ```
+    pub fn socket_path<T: AsRef<Path>>(mut self, socket_path: T) -> Self {
+        self.socket_path = socket_path.as_ref().to_owned();
+        self
+    }
+
+    /// Sets the `SYSLOG_IDENTIFIER` field sent with every record.
+    ///
+    /// journald uses this as the program name when displaying records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let log = fern::Journald::new().syslog_identifier("my-program");
+    /// ```
+    pub fn syslog_identifier<T: Into<Cow<'static, str>>>(mut self, identifier: T) -> Self {
+        self.syslog_identifier = Some(identifier.into());
+        self
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "journald"))]
+impl Default for Journald {
+    /// Returns a journal logger using the default socket.
+    ///
+    /// Equivalent to [`Journald::new`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "journald"))]
+impl From<Journald> for Output {
+    /// Create an output logger which sends records to the systemd journal.
+    fn from(config: Journald) -> Self {
+        Output(OutputInner::Journald { config })
+    }
+}
+
+/// Syslog message severity, as defined by [RFC 5424].
+///
+/// By default, log levels are translated trace => debug, debug => debug,
+/// info => informational, warn => warning, and error => error. See the
+/// [`From<log::Level>`] implementation.
+///
+/// [RFC 5424]: https://tools.ietf.org/html/rfc5424#section-6.2.1
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SyslogSeverity {
+    /// System is unusable.
+    Emergency,
+    /// Action must be taken immediately.
+    Alert,
+    /// Critical conditions.
+    Critical,
+    /// Error conditions.
+    Error,
+    /// Warning conditions.
+    Warning,
+    /// Normal but significant condition.
+    Notice,
+    /// Informational messages.
+    Informational,
+    /// Debug-level messages.
+    Debug,
+}
+
+impl From<log::Level> for SyslogSeverity {
+    /// Translates a log level into the default severity for it.
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => SyslogSeverity::Error,
+            log::Level::Warn => SyslogSeverity::Warning,
+            log::Level::Info => SyslogSeverity::Informational,
+            log::Level::Debug | log::Level::Trace => SyslogSeverity::Debug,
+        }
+    }
+}
+
+impl SyslogSeverity {
+    /// Returns the numeric value of this severity, as used in syslog
+    /// priorities. `Emergency` is 0 and `Debug` is 7.
+    pub fn code(self) -> u8 {
+        match self {
+            SyslogSeverity::Emergency => 0,
+            SyslogSeverity::Alert => 1,
+            SyslogSeverity::Critical => 2,
+            SyslogSeverity::Error => 3,
+            SyslogSeverity::Warning => 4,
+            SyslogSeverity::Notice => 5,
+            SyslogSeverity::Informational => 6,
+            SyslogSeverity::Debug => 7,
+        }
+    }
+}
+
+/// Syslog facility, as defined by `<syslog.h>`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum SyslogFacility {
+    Kern,
+    User,
+    Mail,
+    Daemon,
+    Auth,
+    Syslog,
+    Lpr,
+    News,
+    Uucp,
+    Cron,
+    AuthPriv,
+    Ftp,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+/// Configuration for how log records are translated into syslog severities
+/// and facilities.
+///
+/// Used with [`Output::syslog_mapped`].
+///
+/// Per-target facilities are matched the same way as
+/// [`Dispatch::level_for`]: a facility set for `my_crate::auth` also applies
+/// to `my_crate::auth::session`.
+///
+/// Note that syslog 4 loggers don't allow changing the facility after they're
+/// created, so facilities configured here have no effect on them. The
+/// severity mapping works for all syslog versions.
+///
+/// [`Dispatch::level_for`]: struct.Dispatch.html#method.level_for
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+#[must_use = "builder methods take config by value and thus must be reassigned to variable"]
+pub struct SyslogMapping {
+    severity: Option<Box<SyslogSeverityFn>>,
+    default_facility: Option<SyslogFacility>,
+    facilities: Vec<(Cow<'static, str>, SyslogFacility)>,
+}
+
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+impl SyslogMapping {
+    /// Creates a mapping using the default severities and the syslog
+    /// logger's own facility.
+    pub fn new() -> Self {
+        SyslogMapping {
+            severity: None,
+            default_facility: None,
+            facilities: Vec::new(),
+        }
+    }
+
+    /// Sets the function used to choose a severity for each record.
+    ///
+    /// The default translates the record's level using
+    /// `SyslogSeverity::from`.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// use fern::{SyslogMapping, SyslogSeverity};
+    ///
+    /// let mapping = SyslogMapping::new().severity(|record| {
+    ///     if record.level() == log::Level::Error && record.target().starts_with("db") {
+    ///         SyslogSeverity::Critical
+    ///     } else {
+    ///         record.level().into()
+    ///     }
+    /// });
+    /// ```
+    pub fn severity<F>(mut self, severity: F) -> Self
+    where
+        F: Fn(&log::Record) -> SyslogSeverity + Send + Sync + 'static,
+    {
+        self.severity = Some(Box::new(severity));
+        self
+    }
+
+    /// Sets the facility used for records which don't match any
+    /// [`SyslogMapping::facility_for`] target.
+    ///
+    /// By default, the facility the syslog logger was created with is used.
+    /// syslog 3 loggers don't allow reading this facility back, so when
+    /// using `facility_for` with them, `Facility::User` is assumed unless
+    /// this is set.
+    pub fn facility(mut self, facility: SyslogFacility) -> Self {
+        self.default_facility = Some(facility);
+        self
+    }
+
+    /// Sets the facility used for records from the given target and its
+    /// submodules.
+    ///
+    /// Example usage:
+    ///
+    /// ```
+    /// use fern::{SyslogFacility, SyslogMapping};
+    ///
+    /// let mapping = SyslogMapping::new()
+    ///     .facility_for("my_crate::auth", SyslogFacility::Auth)
+    ///     .facility_for("my_crate::jobs", SyslogFacility::Cron);
+    /// ```
+    pub fn facility_for<T: Into<Cow<'static, str>>>(
+        mut self,
+        target: T,
+        facility: SyslogFacility,
+    ) -> Self {
+        let target = target.into();
+
+        if let Some((index, _)) = self
+            .facilities
+            .iter()
+            .enumerate()
+            .find(|(_, (name, _))| *name == target)
+        {
+            self.facilities.remove(index);
+        }
+
+        self.facilities.push((target, facility));
+        self
+    }
+
+    fn into_mapping(self) -> log_impl::SyslogMapping {
+        log_impl::SyslogMapping {
+            severity: self.severity,
+            default_facility: self.default_facility,
+            facilities: self.facilities.into(),
+        }
+    }
+}
+
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+impl Default for SyslogMapping {
+    /// Returns a mapping with the default severities and facility.
+    ///
+    /// Equivalent to [`SyslogMapping::new`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+impl fmt::Debug for SyslogMapping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SyslogMapping")
+            .field(
+                "severity",
+                &self.severity.as_ref().map(|_| "<severity closure>"),
+            )
+            .field("default_facility", &self.default_facility)
+            .field("facilities", &self.facilities)
+            .finish()
+    }
+}
+
+/// How to handle messages containing newlines.
+///
+/// Line-oriented log parsers usually treat each line as a separate record, so
+/// multiline messages such as stack traces or pretty-printed structs can be
+/// split up or misattributed. A single trailing newline is always removed
+/// first, and lines may end in either `\n` or `\r\n`.
+///
+/// See [`Dispatch::multiline`] and [`Output::multiline`].
+///
+/// [`Dispatch::multiline`]: struct.Dispatch.html#method.multiline
+/// [`Output::multiline`]: struct.Output.html#method.multiline
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Multiline {
+    /// Starts each line after the first with the given prefix.
+    Indent(Cow<'static, str>),
+    /// Replaces each newline with `\n`, and each carriage return with `\r`,
+    /// so the message is written on a single line. Backslashes are doubled,
+    /// so a message containing a literal `\n` stays distinguishable.
+    Escape,
+    /// Formats and logs each line as a separate message, repeating any
+    /// header added by the formatter.
+    RepeatHeader,
+}
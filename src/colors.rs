@@ -1,4 +1,4 @@
-//! Support for ANSI terminal colors via the colored crate.
+//! Support for ANSI terminal colors.
 //!
 //! To enable support for colors, add the `"colored"` feature in your
 //! `Cargo.toml`:
@@ -48,17 +48,413 @@
 //!
 //! ---
 //!
-//! Coloring levels is nice, but the alternative is good too. For an example of an
-//! application coloring each entire log line with the right color, see
-//! [examples/pretty-colored.rs][ex].
+//! Coloring levels is nice, but the alternative is good too.
+//! [`ColoredLevelConfig::color_line`] colors any text, such as the entire
+//! log line, with the level's color. Styles used within the line, such as
+//! the colored level below, don't end the line's color:
+//!
+//! ```
+//! use fern::colors::{self, Color, ColoredLevelConfig};
+//!
+//! let line_colors = ColoredLevelConfig::new().trace(Color::BrightBlack);
+//! let level_colors = line_colors.info(Color::Green);
+//!
+//! fern::Dispatch::new()
+//!     .format(move |out, message, record| {
+//!         out.finish(format_args!(
+//!             "{}",
+//!             line_colors.color_line(
+//!                 record.level(),
+//!                 format_args!(
+//!                     "[{} {}] {}",
+//!                     level_colors.color(record.level()),
+//!                     colors::target(record.target()),
+//!                     message,
+//!                 ),
+//!             ),
+//!         ))
+//!     })
+//!     # .into_log();
+//! ```
+//!
+//! [`Style`] combines foreground and background colors with bold, dim and
+//! italic text, for coloring anything else:
+//!
+//! ```
+//! use fern::colors::{Color, Style};
+//!
+//! let highlight = Style::new().fg(Color::Black).bg(Color::Yellow).bold();
+//! println!("{} found", highlight.paint("3 matches"));
+//! ```
+//!
+//! [`TargetColors`] gives each target its own color, so that records from
+//! one subsystem are easy to follow:
+//!
+//! ```
+//! let targets = fern::colors::TargetColors::new();
+//!
+//! fern::Dispatch::new()
+//!     .format(move |out, message, record| {
+//!         out.finish(format_args!("[{}] {}", targets.paint(record.target()), message))
+//!     })
+//!     # .into_log();
+//! ```
+//!
+//! For a complete example of an application coloring each entire log line
+//! with the right color, see [examples/pretty-colored.rs][ex].
+//!
+//! ---
+//!
+//! The same formatter can feed both terminals and files: colors are only
+//! written to outputs which support them. See [`enabled`] for details.
 //!
 //! [`ColoredLevelConfig`]: struct.ColoredLevelConfig.html
+//! [`ColoredLevelConfig::color_line`]: struct.ColoredLevelConfig.html#method.color_line
+//! [`Style`]: struct.Style.html
+//! [`TargetColors`]: struct.TargetColors.html
+//! [`enabled`]: fn.enabled.html
 //! [ex]: https://github.com/daboross/fern/blob/fern-0.7.0/examples/pretty-colored.rs
-use std::fmt;
+use std::{
+    borrow::Cow,
+    env,
+    ffi::OsString,
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
 
-pub use colored::Color;
 use log::Level;
 
+use crate::log_impl::OUTPUT_COLORS;
+
+/// A terminal color.
+///
+/// This supports the 16 standard colors, the 256 colors of xterm's palette
+/// and 24-bit RGB colors. Not all terminals support the latter two.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    #[allow(missing_docs)]
+    Black,
+    #[allow(missing_docs)]
+    Red,
+    #[allow(missing_docs)]
+    Green,
+    #[allow(missing_docs)]
+    Yellow,
+    #[allow(missing_docs)]
+    Blue,
+    #[allow(missing_docs)]
+    Magenta,
+    #[allow(missing_docs)]
+    Cyan,
+    #[allow(missing_docs)]
+    White,
+    /// Gray, in most terminals.
+    BrightBlack,
+    #[allow(missing_docs)]
+    BrightRed,
+    #[allow(missing_docs)]
+    BrightGreen,
+    #[allow(missing_docs)]
+    BrightYellow,
+    #[allow(missing_docs)]
+    BrightBlue,
+    #[allow(missing_docs)]
+    BrightMagenta,
+    #[allow(missing_docs)]
+    BrightCyan,
+    #[allow(missing_docs)]
+    BrightWhite,
+    /// A color from xterm's 256 color palette.
+    ///
+    /// The first 16 are the standard colors, followed by a 6x6x6 color cube
+    /// and 24 shades of gray.
+    Ansi256(u8),
+    /// A 24-bit RGB color.
+    TrueColor {
+        #[allow(missing_docs)]
+        r: u8,
+        #[allow(missing_docs)]
+        g: u8,
+        #[allow(missing_docs)]
+        b: u8,
+    },
+}
+
+/// Foreground codes of the 16 standard colors. Background codes are 10
+/// higher.
+const STANDARD_FG_CODES: [&str; 16] = [
+    "30", "31", "32", "33", "34", "35", "36", "37", "90", "91", "92", "93", "94", "95", "96", "97",
+];
+
+const STANDARD_BG_CODES: [&str; 16] = [
+    "40", "41", "42", "43", "44", "45", "46", "47", "100", "101", "102", "103", "104", "105",
+    "106", "107",
+];
+
+impl Color {
+    /// Returns the SGR parameters setting this color as the foreground
+    /// color, such as `31` for red.
+    pub fn to_fg_str(&self) -> Cow<'static, str> {
+        match self.standard_index() {
+            Some(index) => Cow::Borrowed(STANDARD_FG_CODES[index]),
+            None => Cow::Owned(CodeDisplay(*self, Layer::Foreground).to_string()),
+        }
+    }
+
+    /// Returns the SGR parameters setting this color as the background
+    /// color, such as `41` for red.
+    pub fn to_bg_str(&self) -> Cow<'static, str> {
+        match self.standard_index() {
+            Some(index) => Cow::Borrowed(STANDARD_BG_CODES[index]),
+            None => Cow::Owned(CodeDisplay(*self, Layer::Background).to_string()),
+        }
+    }
+
+    fn standard_index(&self) -> Option<usize> {
+        use Color::*;
+
+        let index = match *self {
+            Black => 0,
+            Red => 1,
+            Green => 2,
+            Yellow => 3,
+            Blue => 4,
+            Magenta => 5,
+            Cyan => 6,
+            White => 7,
+            BrightBlack => 8,
+            BrightRed => 9,
+            BrightGreen => 10,
+            BrightYellow => 11,
+            BrightBlue => 12,
+            BrightMagenta => 13,
+            BrightCyan => 14,
+            BrightWhite => 15,
+            Ansi256(_) | TrueColor { .. } => return None,
+        };
+        Some(index)
+    }
+}
+
+/// Parses the names of the 16 standard colors, ignoring case, such as
+/// `"red"` or `"bright blue"`. `"purple"` is accepted for magenta.
+///
+/// This accepts the same names as `colored`'s `Color` did.
+impl FromStr for Color {
+    type Err = ();
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src.to_lowercase().as_str() {
+            "black" => Ok(Color::Black),
+            "red" => Ok(Color::Red),
+            "green" => Ok(Color::Green),
+            "yellow" => Ok(Color::Yellow),
+            "blue" => Ok(Color::Blue),
+            "magenta" | "purple" => Ok(Color::Magenta),
+            "cyan" => Ok(Color::Cyan),
+            "white" => Ok(Color::White),
+            "bright black" => Ok(Color::BrightBlack),
+            "bright red" => Ok(Color::BrightRed),
+            "bright green" => Ok(Color::BrightGreen),
+            "bright yellow" => Ok(Color::BrightYellow),
+            "bright blue" => Ok(Color::BrightBlue),
+            "bright magenta" | "bright purple" => Ok(Color::BrightMagenta),
+            "bright cyan" => Ok(Color::BrightCyan),
+            "bright white" => Ok(Color::BrightWhite),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Parses a color name as [`FromStr`] does, falling back to white for
+/// unknown names.
+impl<'a> From<&'a str> for Color {
+    fn from(src: &'a str) -> Self {
+        src.parse().unwrap_or(Color::White)
+    }
+}
+
+/// Parses a color name as [`FromStr`] does, falling back to white for
+/// unknown names.
+impl From<String> for Color {
+    fn from(src: String) -> Self {
+        src.parse().unwrap_or(Color::White)
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Layer {
+    Foreground,
+    Background,
+}
+
+/// Displays the SGR parameters for a color, without allocating.
+struct CodeDisplay(Color, Layer);
+
+impl fmt::Display for CodeDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let CodeDisplay(color, layer) = *self;
+        let (codes, extended) = match layer {
+            Layer::Foreground => (&STANDARD_FG_CODES, 38),
+            Layer::Background => (&STANDARD_BG_CODES, 48),
+        };
+        match color {
+            Color::Ansi256(index) => write!(f, "{};5;{}", extended, index),
+            Color::TrueColor { r, g, b } => write!(f, "{};2;{};{};{}", extended, r, g, b),
+            // standard_index is only `None` for the two above.
+            _ => f.write_str(codes[color.standard_index().unwrap_or(0)]),
+        }
+    }
+}
+
+/// Returns whether colors are written by [`WithFgColor`] and [`Styled`] on
+/// the current thread.
+///
+/// While logging, this depends on the output the message is being written
+/// to:
+///
+/// - stdout and stderr, including [`Output::std_split`], use colors if the
+///   stream is a terminal. See [`stdout_enabled`].
+/// - nested dispatches use colors if all of their outputs do.
+/// - all other outputs, such as files, syslog, channels and outputs made
+///   from other [`Log`] implementations, never use colors.
+///
+/// Outside of logging, colors are always enabled.
+///
+/// Since colors are decided when the message is written, this relies on the
+/// formatted message being written by each output. Dispatches rendering
+/// messages before passing them to outputs, through
+/// [`Dispatch::render_once`], [`Dispatch::redact`] or
+/// [`Dispatch::max_message_len`], only use colors if all of their outputs
+/// do.
+///
+/// [`WithFgColor`]: struct.WithFgColor.html
+/// [`Styled`]: struct.Styled.html
+/// [`Output::std_split`]: ../struct.Output.html#method.std_split
+/// [`stdout_enabled`]: fn.stdout_enabled.html
+/// [`Log`]: https://docs.rs/log/0.4/log/trait.Log.html
+/// [`Dispatch::render_once`]: ../struct.Dispatch.html#method.render_once
+/// [`Dispatch::redact`]: ../struct.Dispatch.html#method.redact
+/// [`Dispatch::max_message_len`]: ../struct.Dispatch.html#method.max_message_len
+pub fn enabled() -> bool {
+    OUTPUT_COLORS
+        .try_with(|colors| colors.get())
+        .ok()
+        .flatten()
+        .unwrap_or(true)
+}
+
+/// Returns whether colors should be written to stdout.
+///
+/// This is `true` if stdout is a terminal, unless overridden by environment
+/// variables:
+///
+/// - `NO_COLOR` set to any non-empty value disables colors.
+/// - otherwise, `CLICOLOR_FORCE` set to anything but `0` enables colors,
+///   even if stdout isn't a terminal.
+/// - otherwise, `CLICOLOR=0` disables colors.
+///
+/// This is only checked once.
+pub fn stdout_enabled() -> bool {
+    static STDOUT: AtomicU8 = AtomicU8::new(UNKNOWN);
+    cached(&STDOUT, || is_terminal(Stream::Stdout))
+}
+
+/// Returns whether colors should be written to stderr.
+///
+/// This works the same way as [`stdout_enabled`].
+///
+/// [`stdout_enabled`]: fn.stdout_enabled.html
+pub fn stderr_enabled() -> bool {
+    static STDERR: AtomicU8 = AtomicU8::new(UNKNOWN);
+    cached(&STDERR, || is_terminal(Stream::Stderr))
+}
+
+#[derive(Copy, Clone)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Returns whether the stream is a terminal.
+#[cfg(unix)]
+fn is_terminal(stream: Stream) -> bool {
+    use std::os::raw::c_int;
+
+    extern "C" {
+        fn isatty(fd: c_int) -> c_int;
+    }
+
+    let fd = match stream {
+        Stream::Stdout => 1,
+        Stream::Stderr => 2,
+    };
+    // safe: isatty only inspects the file descriptor, and fails for invalid
+    // ones.
+    unsafe { isatty(fd) != 0 }
+}
+
+/// Returns whether the stream is a console.
+#[cfg(windows)]
+fn is_terminal(stream: Stream) -> bool {
+    use std::os::raw::c_void;
+
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(std_handle: u32) -> *mut c_void;
+        fn GetConsoleMode(console: *mut c_void, mode: *mut u32) -> i32;
+    }
+
+    let std_handle = match stream {
+        Stream::Stdout => STD_OUTPUT_HANDLE,
+        Stream::Stderr => STD_ERROR_HANDLE,
+    };
+    // safe: GetConsoleMode fails for invalid handles and handles which
+    // aren't consoles.
+    unsafe {
+        let mut mode = 0;
+        GetConsoleMode(GetStdHandle(std_handle), &mut mode) != 0
+    }
+}
+
+/// Other platforms have no terminals to detect.
+#[cfg(not(any(unix, windows)))]
+fn is_terminal(_: Stream) -> bool {
+    false
+}
+
+const UNKNOWN: u8 = 0;
+const DISABLED: u8 = 1;
+const ENABLED: u8 = 2;
+
+fn cached(cache: &AtomicU8, is_terminal: impl FnOnce() -> bool) -> bool {
+    match cache.load(Ordering::Relaxed) {
+        UNKNOWN => {
+            let enabled = from_env(is_terminal(), |name| env::var_os(name));
+            cache.store(if enabled { ENABLED } else { DISABLED }, Ordering::Relaxed);
+            enabled
+        }
+        cached => cached == ENABLED,
+    }
+}
+
+/// Applies the `NO_COLOR`, `CLICOLOR_FORCE` and `CLICOLOR` overrides.
+fn from_env(is_terminal: bool, var: impl Fn(&str) -> Option<OsString>) -> bool {
+    if var("NO_COLOR").map_or(false, |value| !value.is_empty()) {
+        false
+    } else if var("CLICOLOR_FORCE").map_or(false, |value| !value.is_empty() && value != "0") {
+        true
+    } else if var("CLICOLOR").map_or(false, |value| value == "0") {
+        false
+    } else {
+        is_terminal
+    }
+}
+
 /// Extension crate allowing the use of `.colored` on Levels.
 trait ColoredLogLevel {
     /// Colors this log level with the given color.
@@ -73,9 +469,6 @@ trait ColoredLogLevel {
 /// reset the color.
 ///
 /// `WithFgColor` instances can be created and displayed without any allocation.
-// this is necessary in order to avoid using colored::ColorString, which has a
-// Display implementation involving many allocations, and would involve two
-// more string allocations even to create it.
 //
 // [`fmt::Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
 pub struct WithFgColor<T>
@@ -91,13 +484,356 @@ where
     T: fmt::Display,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "\x1B[{}m", self.color.to_fg_str())?;
+        if !enabled() {
+            return fmt::Display::fmt(&self.text, f);
+        }
+        write!(f, "\x1B[{}m", CodeDisplay(self.color, Layer::Foreground))?;
         fmt::Display::fmt(&self.text, f)?;
         write!(f, "\x1B[0m")?;
         Ok(())
     }
 }
 
+/// A combination of colors and text attributes.
+///
+/// The default style has no colors or attributes, and displays text
+/// unchanged.
+///
+/// Example usage:
+///
+/// ```
+/// use fern::colors::{Color, Style};
+///
+/// let style = Style::new().fg(Color::Red).bold();
+/// assert_eq!(style.paint("error").to_string(), "\x1B[1;31merror\x1B[0m");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[must_use = "builder methods take style by value and thus must be reassigned to variable"]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+    strikethrough: bool,
+}
+
+/// Text displayed with a [`Style`].
+///
+/// This implements [`fmt::Display`], writing ANSI escape codes for the style
+/// before the text and resetting them after it. Any reset written by the
+/// text itself, for example by a [`WithFgColor`] inside a colored line, is
+/// followed by the style's codes again, so that the rest of the text stays
+/// styled. This isn't done when formatting with a width or precision, which
+/// are applied to the text instead.
+///
+/// `Styled` instances can be created and displayed without any allocation.
+///
+/// [`Style`]: struct.Style.html
+/// [`WithFgColor`]: struct.WithFgColor.html
+/// [`fmt::Display`]: https://doc.rust-lang.org/std/fmt/trait.Display.html
+pub struct Styled<T>
+where
+    T: fmt::Display,
+{
+    text: T,
+    style: Style,
+}
+
+const RESET: &str = "\x1B[0m";
+
+impl Style {
+    /// Creates a style without any colors or attributes.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the foreground color.
+    pub fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Sets the background color.
+    pub fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// Makes text bold.
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Makes text dim.
+    pub fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    /// Makes text italic.
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Underlines text.
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    /// Swaps the foreground and background colors.
+    pub fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Strikes text through.
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Returns `true` if this style has no colors or attributes.
+    pub fn is_plain(&self) -> bool {
+        *self == Style::default()
+    }
+
+    /// Returns the given text with this style, for displaying.
+    pub fn paint<T: fmt::Display>(self, text: T) -> Styled<T> {
+        Styled { text, style: self }
+    }
+
+    /// Writes the escape code setting this style.
+    fn write_prefix(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        let attributes = [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.reverse, "7"),
+            (self.strikethrough, "9"),
+        ];
+
+        f.write_str("\x1B[")?;
+        let mut separator = "";
+        for &(enabled, code) in &attributes {
+            if enabled {
+                write!(f, "{}{}", separator, code)?;
+                separator = ";";
+            }
+        }
+        if let Some(color) = self.fg {
+            write!(f, "{}{}", separator, CodeDisplay(color, Layer::Foreground))?;
+            separator = ";";
+        }
+        if let Some(color) = self.bg {
+            write!(f, "{}{}", separator, CodeDisplay(color, Layer::Background))?;
+        }
+        f.write_char('m')
+    }
+}
+
+impl<T> fmt::Display for Styled<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.style.is_plain() || !enabled() {
+            return fmt::Display::fmt(&self.text, f);
+        }
+        self.style.write_prefix(f)?;
+        if f.width().is_some() || f.precision().is_some() {
+            fmt::Display::fmt(&self.text, f)?;
+        } else {
+            use fmt::Write;
+            write!(
+                Reapply {
+                    f: &mut *f,
+                    style: &self.style,
+                },
+                "{}",
+                self.text
+            )?;
+        }
+        f.write_str(RESET)
+    }
+}
+
+/// Writer applying a style again after each reset.
+struct Reapply<'a, 'f> {
+    f: &'a mut fmt::Formatter<'f>,
+    style: &'a Style,
+}
+
+impl fmt::Write for Reapply<'_, '_> {
+    fn write_str(&mut self, mut s: &str) -> fmt::Result {
+        while let Some(index) = s.find(RESET) {
+            let end = index + RESET.len();
+            self.f.write_str(&s[..end])?;
+            self.style.write_prefix(self.f)?;
+            s = &s[end..];
+        }
+        self.f.write_str(s)
+    }
+}
+
+/// Returns a timestamp styled to stand back from the message: dimmed.
+///
+/// ```
+/// # let timestamp = "2024-01-01T00:00:00Z";
+/// # let message = "";
+/// println!("{} {}", fern::colors::timestamp(timestamp), message);
+/// ```
+pub fn timestamp<T: fmt::Display>(timestamp: T) -> Styled<T> {
+    Style::new().dim().paint(timestamp)
+}
+
+/// Returns a record's target styled to stand back from the message: dimmed
+/// and italic.
+///
+/// ```
+/// # let record = log::Record::builder().build();
+/// println!("{}", fern::colors::target(record.target()));
+/// ```
+pub fn target<T: fmt::Display>(target: T) -> Styled<T> {
+    Style::new().dim().italic().paint(target)
+}
+
+/// Colors readable on both dark and light backgrounds: the standard colors
+/// except black, white, bright black (gray), bright yellow and bright white.
+const DEFAULT_TARGET_PALETTE: &[Color] = &[
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+];
+
+/// Assigns each target a color from a palette, based on a hash of its name.
+///
+/// The same target always gets the same color, across runs, platforms and
+/// fern versions, so colors can be learned. Different targets may share a
+/// color, especially with small palettes.
+///
+/// [`TargetColors::new`] uses 11 of the 16 standard colors, leaving out
+/// those hard to read on dark or light backgrounds.
+/// [`TargetColors::ansi256`] and [`TargetColors::with_palette`] use other
+/// palettes.
+///
+/// Example usage:
+///
+/// ```
+/// use fern::colors::{Color, TargetColors};
+///
+/// let targets = TargetColors::new();
+/// assert_eq!(targets.color("my_app::db"), targets.color("my_app::db"));
+///
+/// let custom = TargetColors::with_palette(vec![Color::Ansi256(33), Color::Ansi256(208)]);
+/// println!("{}", custom.paint("my_app::db"));
+/// ```
+///
+/// [`TargetColors::new`]: #method.new
+/// [`TargetColors::ansi256`]: #method.ansi256
+/// [`TargetColors::with_palette`]: #method.with_palette
+#[derive(Clone, Debug)]
+pub struct TargetColors {
+    palette: Cow<'static, [Color]>,
+}
+
+impl TargetColors {
+    /// Creates a configuration using the standard colors readable on both
+    /// dark and light backgrounds: red, green, yellow, blue, magenta, cyan,
+    /// bright red, bright green, bright blue, bright magenta and bright cyan.
+    ///
+    /// This leaves out black, white, bright black (gray), bright yellow and
+    /// bright white.
+    pub fn new() -> Self {
+        TargetColors {
+            palette: Cow::Borrowed(DEFAULT_TARGET_PALETTE),
+        }
+    }
+
+    /// Creates a configuration using colors from xterm's 256 color palette,
+    /// for more distinct colors on terminals supporting them.
+    ///
+    /// This includes colors from the 6x6x6 color cube which are neither too
+    /// dark nor too light, and aren't close to gray.
+    pub fn ansi256() -> Self {
+        const LEVELS: [u32; 6] = [0, 95, 135, 175, 215, 255];
+
+        let palette = (0..216u8)
+            .filter(|&index| {
+                let (r, g, b) = (
+                    LEVELS[usize::from(index / 36)],
+                    LEVELS[usize::from(index / 6 % 6)],
+                    LEVELS[usize::from(index % 6)],
+                );
+                let luma = (2126 * r + 7152 * g + 722 * b) / 10_000;
+                let saturation = r.max(g).max(b) - r.min(g).min(b);
+                (70..=190).contains(&luma) && saturation >= 120
+            })
+            .map(|index| Color::Ansi256(16 + index))
+            .collect();
+        TargetColors {
+            palette: Cow::Owned(palette),
+        }
+    }
+
+    /// Creates a configuration using the given colors.
+    ///
+    /// The palette is used as-is; avoid colors with low contrast against
+    /// the terminal's background.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the palette is empty.
+    pub fn with_palette<P: Into<Cow<'static, [Color]>>>(palette: P) -> Self {
+        let palette = palette.into();
+        assert!(
+            !palette.is_empty(),
+            "target color palette must not be empty"
+        );
+        TargetColors { palette }
+    }
+
+    /// Returns the color for the given target.
+    pub fn color(&self, target: &str) -> Color {
+        // FNV-1a, which unlike std's hashers is stable across versions.
+        let hash = target
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+            });
+        self.palette[(hash % self.palette.len() as u64) as usize]
+    }
+
+    /// Returns the target, colored with its color.
+    pub fn paint<'a>(&self, target: &'a str) -> WithFgColor<&'a str> {
+        WithFgColor {
+            text: target,
+            color: self.color(target),
+        }
+    }
+}
+
+impl Default for TargetColors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Configuration specifying colors a log level can be colored as.
 ///
 /// Example usage setting custom 'info' and 'debug' colors:
@@ -156,10 +892,6 @@ impl ColoredLevelConfig {
     /// [`ColoredLevelConfig::default`]: #method.default
     #[inline]
     pub fn new() -> Self {
-        #[cfg(windows)]
-        {
-            let _ = colored::control::set_virtual_terminal(true);
-        }
         Self::default()
     }
 
@@ -168,7 +900,7 @@ impl ColoredLevelConfig {
     /// The default color is [`Color::Red`].
     ///
     /// [`Error`]: https://docs.rs/log/0.4/log/enum.Level.html#variant.Error
-    /// [`Color::Red`]: https://docs.rs/colored/1/colored/enum.Color.html#variant.Red
+    /// [`Color::Red`]: enum.Color.html#variant.Red
     pub fn error(mut self, error: Color) -> Self {
         self.error = error;
         self
@@ -179,7 +911,7 @@ impl ColoredLevelConfig {
     /// The default color is [`Color::Yellow`].
     ///
     /// [`Warn`]: https://docs.rs/log/0.4/log/enum.Level.html#variant.Warn
-    /// [`Color::Yellow`]: https://docs.rs/colored/1/colored/enum.Color.html#variant.Yellow
+    /// [`Color::Yellow`]: enum.Color.html#variant.Yellow
     pub fn warn(mut self, warn: Color) -> Self {
         self.warn = warn;
         self
@@ -190,7 +922,7 @@ impl ColoredLevelConfig {
     /// The default color is [`Color::White`].
     ///
     /// [`Info`]: https://docs.rs/log/0.4/log/enum.Level.html#variant.Info
-    /// [`Color::White`]: https://docs.rs/colored/1/colored/enum.Color.html#variant.White
+    /// [`Color::White`]: enum.Color.html#variant.White
     pub fn info(mut self, info: Color) -> Self {
         self.info = info;
         self
@@ -201,7 +933,7 @@ impl ColoredLevelConfig {
     /// The default color is [`Color::White`].
     ///
     /// [`Debug`]: https://docs.rs/log/0.4/log/enum.Level.html#variant.Debug
-    /// [`Color::White`]: https://docs.rs/colored/1/colored/enum.Color.html#variant.White
+    /// [`Color::White`]: enum.Color.html#variant.White
     pub fn debug(mut self, debug: Color) -> Self {
         self.debug = debug;
         self
@@ -212,7 +944,7 @@ impl ColoredLevelConfig {
     /// The default color is [`Color::White`].
     ///
     /// [`Trace`]: https://docs.rs/log/0.4/log/enum.Level.html#variant.Trace
-    /// [`Color::White`]: https://docs.rs/colored/1/colored/enum.Color.html#variant.White
+    /// [`Color::White`]: enum.Color.html#variant.White
     pub fn trace(mut self, trace: Color) -> Self {
         self.trace = trace;
         self
@@ -222,15 +954,43 @@ impl ColoredLevelConfig {
     /// corresponding to it's level.
     ///
     /// The structure returned is opaque, but will print the Level surrounded
-    /// by ANSI color codes when displayed. This will work correctly for
-    /// UNIX terminals, but due to a lack of support from the [`colored`]
-    /// crate, this will not function in Windows.
+    /// by ANSI color codes when displayed. On Windows, this requires Windows
+    /// 10 or later, where [`ColoredLevelConfig::new`] enables support for
+    /// ANSI codes in the console.
     ///
-    /// [`colored`]: https://github.com/mackwic/colored
+    /// [`ColoredLevelConfig::new`]: #method.new
     pub fn color(&self, level: Level) -> WithFgColor<Level> {
         level.colored(self.get_color(&level))
     }
 
+    /// Colors the given text, such as an entire log line, with the color in
+    /// this configuration corresponding to the level.
+    ///
+    /// Unlike [`color`], this keeps coloring the rest of the text after any
+    /// styled text within it, such as the level colored by another
+    /// configuration. See [`Styled`].
+    ///
+    /// ```
+    /// # let colors = fern::colors::ColoredLevelConfig::new();
+    /// fern::Dispatch::new()
+    ///     .format(move |out, message, record| {
+    ///         out.finish(format_args!(
+    ///             "{}",
+    ///             colors.color_line(
+    ///                 record.level(),
+    ///                 format_args!("[{}] {}", record.level(), message),
+    ///             ),
+    ///         ))
+    ///     })
+    ///     # .into_log();
+    /// ```
+    ///
+    /// [`color`]: #method.color
+    /// [`Styled`]: struct.Styled.html
+    pub fn color_line<T: fmt::Display>(&self, level: Level, text: T) -> Styled<T> {
+        Style::new().fg(self.get_color(&level)).paint(text)
+    }
+
     /// Retrieves the color that a log level should be colored as.
     pub fn get_color(&self, level: &Level) -> Color {
         match *level {
@@ -257,10 +1017,12 @@ impl Default for ColoredLevelConfig {
     /// [`Info`]: https://docs.rs/log/0.4/log/enum.Level.html#variant.Info
     /// [`Debug`]: https://docs.rs/log/0.4/log/enum.Level.html#variant.Debug
     /// [`Trace`]: https://docs.rs/log/0.4/log/enum.Level.html#variant.Trace
-    /// [`Color::White`]: https://docs.rs/colored/1/colored/enum.Color.html#variant.White
-    /// [`Color::Yellow`]: https://docs.rs/colored/1/colored/enum.Color.html#variant.Yellow
-    /// [`Color::Red`]: https://docs.rs/colored/1/colored/enum.Color.html#variant.Red
+    /// [`Color::White`]: enum.Color.html#variant.White
+    /// [`Color::Yellow`]: enum.Color.html#variant.Yellow
+    /// [`Color::Red`]: enum.Color.html#variant.Red
     fn default() -> Self {
+        #[cfg(windows)]
+        enable_virtual_terminal();
         ColoredLevelConfig {
             error: Color::Red,
             warn: Color::Yellow,
@@ -271,6 +1033,36 @@ impl Default for ColoredLevelConfig {
     }
 }
 
+/// Enables processing of ANSI escape codes by the Windows console, for
+/// stdout and stderr.
+#[cfg(windows)]
+fn enable_virtual_terminal() {
+    use std::os::raw::c_void;
+
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(std_handle: u32) -> *mut c_void;
+        fn GetConsoleMode(console: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console: *mut c_void, mode: u32) -> i32;
+    }
+
+    for &std_handle in &[STD_OUTPUT_HANDLE, STD_ERROR_HANDLE] {
+        // safe: the handle is checked by GetConsoleMode, which fails for
+        // invalid handles and handles which aren't consoles.
+        unsafe {
+            let console = GetStdHandle(std_handle);
+            let mut mode = 0;
+            if GetConsoleMode(console, &mut mode) != 0 {
+                SetConsoleMode(console, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING);
+            }
+        }
+    }
+}
+
 impl ColoredLogLevel for Level {
     fn colored(&self, color: Color) -> WithFgColor<Level> {
         WithFgColor { text: *self, color }
@@ -278,46 +1070,75 @@ impl ColoredLogLevel for Level {
 }
 
 #[cfg(test)]
-#[cfg(not(windows))]
 mod test {
-    use colored::{Color::*, Colorize};
+    use std::ffi::OsString;
 
-    use super::WithFgColor;
+    use super::{from_env, Color, Color::*, Style, TargetColors, WithFgColor};
 
     #[test]
-    fn fg_color_matches_colored_behavior() {
-        for &color in &[
-            Black,
-            Red,
-            Green,
-            Yellow,
-            Blue,
-            Magenta,
-            Cyan,
-            White,
-            BrightBlack,
-            BrightRed,
-            BrightGreen,
-            BrightYellow,
-            BrightBlue,
-            BrightMagenta,
-            BrightCyan,
-            BrightWhite,
-        ] {
-            colored::control::SHOULD_COLORIZE.set_override(true);
+    fn color_parses_names() {
+        let cases = [
+            ("black", Black),
+            ("Red", Red),
+            ("purple", Magenta),
+            ("BRIGHT BLUE", BrightBlue),
+            ("bright purple", BrightMagenta),
+            ("bright white", BrightWhite),
+        ];
+        for &(name, color) in &cases {
+            assert_eq!(name.parse::<Color>(), Ok(color));
+            assert_eq!(Color::from(name), color);
+            assert_eq!(Color::from(name.to_owned()), color);
+        }
+
+        assert_eq!("orange".parse::<Color>(), Err(()));
+        assert_eq!("bright_red".parse::<Color>(), Err(()));
+        assert_eq!(Color::from("orange"), White);
+    }
+
+    #[test]
+    fn fg_color_writes_sgr_codes() {
+        let cases = [
+            (Black, "30"),
+            (Red, "31"),
+            (Green, "32"),
+            (Yellow, "33"),
+            (Blue, "34"),
+            (Magenta, "35"),
+            (Cyan, "36"),
+            (White, "37"),
+            (BrightBlack, "90"),
+            (BrightRed, "91"),
+            (BrightGreen, "92"),
+            (BrightYellow, "93"),
+            (BrightBlue, "94"),
+            (BrightMagenta, "95"),
+            (BrightCyan, "96"),
+            (BrightWhite, "97"),
+            (Ansi256(208), "38;5;208"),
+            (TrueColor { r: 1, g: 2, b: 3 }, "38;2;1;2;3"),
+        ];
+        for &(color, code) in &cases {
             assert_eq!(
-                format!("{}", "test".color(color)),
-                format!(
-                    "{}",
-                    WithFgColor {
-                        text: "test",
-                        color,
-                    }
-                )
+                WithFgColor {
+                    text: "test",
+                    color,
+                }
+                .to_string(),
+                format!("\x1B[{}mtest\x1B[0m", code)
             );
+            assert_eq!(color.to_fg_str(), code);
         }
     }
 
+    #[test]
+    fn bg_color_writes_sgr_codes() {
+        assert_eq!(Red.to_bg_str(), "41");
+        assert_eq!(BrightWhite.to_bg_str(), "107");
+        assert_eq!(Ansi256(0).to_bg_str(), "48;5;0");
+        assert_eq!(TrueColor { r: 255, g: 0, b: 9 }.to_bg_str(), "48;2;255;0;9");
+    }
+
     #[test]
     fn fg_color_respects_formatting_flags() {
         let s = format!(
@@ -331,4 +1152,136 @@ mod test {
         assert!(!s.contains("   test  "));
         assert!(!s.contains("  test   "));
     }
+
+    #[test]
+    fn style_writes_attributes_and_colors() {
+        assert_eq!(Style::new().paint("test").to_string(), "test");
+        assert_eq!(
+            Style::new().fg(Red).paint("test").to_string(),
+            "\x1B[31mtest\x1B[0m"
+        );
+        assert_eq!(
+            Style::new()
+                .fg(BrightWhite)
+                .bg(Blue)
+                .bold()
+                .dim()
+                .italic()
+                .paint("test")
+                .to_string(),
+            "\x1B[1;2;3;97;44mtest\x1B[0m"
+        );
+        assert_eq!(
+            Style::new()
+                .underline()
+                .reverse()
+                .strikethrough()
+                .fg(Ansi256(1))
+                .bg(TrueColor { r: 1, g: 2, b: 3 })
+                .paint("test")
+                .to_string(),
+            "\x1B[4;7;9;38;5;1;48;2;1;2;3mtest\x1B[0m"
+        );
+    }
+
+    #[test]
+    fn style_reapplies_after_nested_reset() {
+        let inner = WithFgColor {
+            text: "INFO",
+            color: Green,
+        };
+        assert_eq!(
+            Style::new()
+                .fg(Yellow)
+                .paint(format_args!("[{}] message", inner))
+                .to_string(),
+            "\x1B[33m[\x1B[32mINFO\x1B[0m\x1B[33m] message\x1B[0m"
+        );
+    }
+
+    #[test]
+    fn style_respects_formatting_flags() {
+        let s = format!("{:>6}", Style::new().bold().paint("test"));
+        assert_eq!(s, "\x1B[1m  test\x1B[0m");
+    }
+
+    #[test]
+    fn env_overrides_terminal_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+        assert!(from_env(true, env(&[])));
+        assert!(!from_env(false, env(&[])));
+        assert!(!from_env(true, env(&[("NO_COLOR", "1")])));
+        assert!(from_env(true, env(&[("NO_COLOR", "")])));
+        assert!(!from_env(
+            true,
+            env(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")])
+        ));
+        assert!(from_env(false, env(&[("CLICOLOR_FORCE", "1")])));
+        assert!(!from_env(false, env(&[("CLICOLOR_FORCE", "0")])));
+        assert!(!from_env(true, env(&[("CLICOLOR", "0")])));
+        assert!(from_env(
+            false,
+            env(&[("CLICOLOR", "0"), ("CLICOLOR_FORCE", "1")])
+        ));
+        assert!(from_env(true, env(&[("CLICOLOR", "1")])));
+    }
+
+    #[test]
+    fn target_colors_are_stable() {
+        let targets = TargetColors::new();
+        assert_eq!(
+            *targets.palette,
+            [
+                Red,
+                Green,
+                Yellow,
+                Blue,
+                Magenta,
+                Cyan,
+                BrightRed,
+                BrightGreen,
+                BrightBlue,
+                BrightMagenta,
+                BrightCyan
+            ][..]
+        );
+        // FNV-1a of "" is its offset basis, 0xcbf29ce484222325, which is 10
+        // modulo the palette size.
+        assert_eq!(targets.color(""), BrightCyan);
+        assert_eq!(targets.color("my_app::db"), targets.color("my_app::db"));
+
+        let palette = [Red, Green, Blue];
+        let targets = TargetColors::with_palette(palette.to_vec());
+        let colors: Vec<Color> = (0..30)
+            .map(|index| targets.color(&format!("target{}", index)))
+            .collect();
+        assert!(colors.iter().all(|color| palette.contains(color)));
+        assert!(palette.iter().all(|color| colors.contains(color)));
+        assert_eq!(
+            targets.paint("a").to_string(),
+            format!("\x1B[{}ma\x1B[0m", targets.color("a").to_fg_str())
+        );
+    }
+
+    #[test]
+    fn ansi256_target_palette_avoids_low_contrast() {
+        let targets = TargetColors::ansi256();
+        assert!(targets.palette.len() >= 32);
+        for color in targets.palette.iter() {
+            match *color {
+                // the darkest and lightest colors of each channel, and grays.
+                Ansi256(16) | Ansi256(231) | Ansi256(59) | Ansi256(102) | Ansi256(145) => {
+                    panic!("{:?} is in the palette", color)
+                }
+                Ansi256(index) => assert!((16..232).contains(&index)),
+                _ => panic!("{:?} is in the palette", color),
+            }
+        }
+    }
 }
@@ -0,0 +1,134 @@
+//! Key-values attached to every record logged on the current thread.
+//!
+//! [`push`] adds a key-value to the current thread's context until the
+//! returned guard is dropped. This is useful for per-request data, such as
+//! request or user ids, which should be included in every log line without
+//! being passed through every function.
+//!
+//! ```
+//! fn handle_request(request_id: u64) {
+//!     let _request = fern::context::push("request_id", request_id);
+//!
+//!     // both records include `request_id`
+//!     log::info!("handling request");
+//!     log::info!("finished");
+//! }
+//! # handle_request(1);
+//! ```
+//!
+//! With the `kv` feature enabled, the context is added to each record's
+//! key-values as it enters the outermost [`Dispatch`], so formatters and
+//! outputs supporting key-values (such as [`Journald`]) see it alongside the
+//! record's own key-values:
+//!
+//! ```
+//! # #[cfg(feature = "kv")]
+//! fern::Dispatch::new()
+//!     .format(|out, message, record| {
+//!         let request_id = record.key_values().get("request_id".into());
+//!         match request_id {
+//!             Some(request_id) => out.finish(format_args!("[{}] {}", request_id, message)),
+//!             None => out.finish(format_args!("{}", message)),
+//!         }
+//!     })
+//!     .chain(std::io::stdout())
+//!     # .into_log();
+//! ```
+//!
+//! Values are formatted when pushed. A key pushed again shadows its earlier
+//! value until the inner guard is dropped. Keys the record itself already has
+//! take precedence when looking them up.
+//!
+//! Without the `kv` feature, the context can be read from formatters through
+//! [`get`] and [`for_each`].
+//!
+//! The context is per-thread, so it isn't carried over to spawned threads, or
+//! across `.await` points of futures moving between threads.
+//!
+//! [`Dispatch`]: ../struct.Dispatch.html
+//! [`Journald`]: ../struct.Journald.html
+use std::{borrow::Cow, fmt, marker::PhantomData, rc::Rc};
+
+use crate::log_impl::CONTEXT;
+
+/// Removes a key-value from the context when dropped.
+///
+/// Guards should be dropped in the reverse order of creation; dropping a
+/// guard also removes any key-values pushed after it.
+///
+/// Created with [`push`].
+#[must_use = "the key-value is removed from the context when the guard is dropped"]
+pub struct ContextGuard {
+    index: usize,
+    // the context is thread-local, so the guard must stay on its thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+/// Adds a key-value to the current thread's context, until the returned
+/// guard is dropped.
+pub fn push<K, V>(key: K, value: V) -> ContextGuard
+where
+    K: Into<Cow<'static, str>>,
+    V: fmt::Display,
+{
+    // format before borrowing, in case formatting itself logs.
+    let value = value.to_string();
+    let key = key.into();
+    CONTEXT.with(|context| {
+        let mut context = context.borrow_mut();
+        // copies the context only if a record being logged still shares it.
+        let context = Rc::make_mut(context.get_or_insert_with(Rc::default));
+        context.push((key, value));
+        ContextGuard {
+            index: context.len() - 1,
+            _not_send: PhantomData,
+        }
+    })
+}
+
+/// Returns the innermost value for `key` in the current thread's context.
+pub fn get(key: &str) -> Option<String> {
+    CONTEXT.with(|context| {
+        context
+            .borrow()
+            .iter()
+            .flat_map(|context| context.iter())
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+    })
+}
+
+/// Calls `f` with each key-value in the current thread's context, outermost
+/// first, including shadowed values.
+///
+/// `f` must not push to the context.
+pub fn for_each<F>(mut f: F)
+where
+    F: FnMut(&str, &str),
+{
+    CONTEXT.with(|context| {
+        for (key, value) in context.borrow().iter().flat_map(|context| context.iter()) {
+            f(key, value);
+        }
+    })
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        // ignore errors - the thread is shutting down.
+        let _ = CONTEXT.try_with(|context| {
+            if let Some(ref mut context) = *context.borrow_mut() {
+                Rc::make_mut(context).truncate(self.index);
+            }
+        });
+    }
+}
+
+impl fmt::Debug for ContextGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ContextGuard")
+            .field("index", &self.index)
+            .finish()
+    }
+}
@@ -12,6 +12,7 @@
 //! feature), and a custom global allocator.
 //!
 //! Upgrade to `fern` 0.7.0, and `colored` 0.2.0 if you depend on it directly, to fix this issue.
+//! Newer versions of fern's `colored` feature don't depend on the `colored` crate at all.
 //!
 //! # Depending on fern
 //!
@@ -33,14 +34,13 @@
 //!
 //! ```no_run
 //! use log::{debug, error, info, trace, warn};
-//! use std::time::SystemTime;
 //!
 //! fn setup_logger() -> Result<(), fern::InitError> {
 //!     fern::Dispatch::new()
 //!         .format(|out, message, record| {
 //!             out.finish(format_args!(
 //!                 "[{} {} {}] {}",
-//!                 humantime::format_rfc3339_seconds(SystemTime::now()),
+//!                 fern::time::Rfc3339::new().now(),
 //!                 record.level(),
 //!                 record.target(),
 //!                 message
@@ -97,27 +97,16 @@
 //! not-yet-written result we can use internally.
 //!
 //! ```
-//! std::time::SystemTime::now()
+//! fern::time::Rfc3339::new().now()
 //! # ;
 //! ```
 //!
-//! [`std::time::SystemTime::now`] retrieves the current time.
-//!
-//! ```
-//! humantime::format_rfc3339_seconds(
-//!     // ...
-//!     # std::time::SystemTime::now()
-//! )
-//! # ;
-//! ```
-//!
-//! [`humantime::format_rfc3339_seconds`] formats the current time into an
-//! RFC3339 timestamp, with second-precision.
+//! [`time::Rfc3339::now`] formats the current time into an RFC3339 timestamp,
+//! with second-precision.
 //!
 //! RFC3339 looks like `2018-02-14T00:28:07Z`, always using UTC, ignoring system
-//! timezone.
+//! timezone. See the [time] module for other precisions.
 //!
-//! `humantime` is a nice light dependency, but only offers this one format.
 //! For more custom time formatting, I recommend
 //! [`jiff`](https://docs.rs/jiff/).
 //!
@@ -218,6 +207,18 @@
 //! See the [syslog] module for examples outputting to the unix syslog, or the
 //! [syslog full example program] for a more realistic sample.
 //!
+//! See the [sd_daemon] module for prefixing lines with priorities journald
+//! understands when running under systemd.
+//!
+//! See the [testing] module for capturing and asserting on log records in
+//! tests, and the [scope] module for overriding the global logger on a single
+//! thread.
+//!
+//! See the [time] module for formatting timestamps without a time library.
+//!
+//! See the [context] module for adding per-request key-values to every
+//! record logged on a thread.
+//!
 //! See the [meta] module for information on getting logging-within-logging
 //! working correctly.
 //!
@@ -226,7 +227,7 @@
 //! [`File`]: std::fs::File
 //! [full example program]: https://github.com/daboross/fern/tree/fern-0.7.0/examples/cmd-program.rs
 //! [syslog full example program]: https://github.com/daboross/fern/tree/fern-0.7.0/examples/syslog.rs
-//! [`humantime::format_rfc3339_seconds`]: https://docs.rs/humantime/2/humantime/fn.format_rfc3339_seconds.html
+//! [`time::Rfc3339::now`]: time::Rfc3339::now
 use std::{
     convert::AsRef,
     fmt,
@@ -242,14 +243,36 @@ use std::collections::HashMap;
 use std::collections::BTreeMap;
 
 pub use crate::{
-    builders::{Dispatch, Output, Panic},
+    builders::{
+        Dispatch, Multiline, Output, Panic, ShutdownHandle, SyslogFacility, SyslogSeverity,
+    },
     errors::InitError,
     log_impl::FormatCallback,
+    memory::{BufferedRecord, MemoryBuffer},
+    panic_hook::install_panic_hook,
+    redact::Redactor,
+    scope::scoped,
 };
 
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+pub use crate::builders::SyslogMapping;
+
 mod builders;
 mod errors;
 mod log_impl;
+mod memory;
+// backtraces require a newer Rust version.
+#[cfg_attr(feature = "backtrace", clippy::msrv = "1.65")]
+mod panic_hook;
+mod redact;
 
 #[cfg(feature = "colored")]
 pub mod colors;
@@ -263,7 +286,12 @@ pub mod colors;
 ))]
 pub mod syslog;
 
+pub mod context;
 pub mod meta;
+pub mod scope;
+pub mod sd_daemon;
+pub mod testing;
+pub mod time;
 
 /// A type alias for a log formatter.
 ///
@@ -278,6 +306,12 @@ pub type Filter = dyn Fn(&log::Metadata) -> bool + Send + Sync + 'static;
 #[cfg(feature = "date-based")]
 pub use crate::builders::DateBased;
 
+#[cfg(all(target_os = "linux", feature = "journald"))]
+pub use crate::builders::Journald;
+
+#[cfg(feature = "kv")]
+type FieldsFn = dyn Fn() -> Vec<(std::borrow::Cow<'static, str>, String)> + Send + Sync;
+
 #[cfg(all(not(windows), feature = "syslog-4"))]
 type Syslog4Rfc3164Logger = syslog4::Logger<syslog4::LoggerBackend, String, syslog4::Formatter3164>;
 
@@ -312,6 +346,17 @@ type Syslog6TransformFn =
 type Syslog7TransformFn =
     dyn Fn(&log::Record) -> (u32, BTreeMap<String, BTreeMap<String, String>>, String) + Send + Sync;
 
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+type SyslogSeverityFn = dyn Fn(&log::Record) -> SyslogSeverity + Send + Sync;
+
 /// Convenience method for opening a log file with common options.
 ///
 /// Equivalent to:
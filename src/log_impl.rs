@@ -1,21 +1,38 @@
 use std::{
     borrow::Cow,
+    cell::{Cell, RefCell},
     collections::HashMap,
     fmt, fs,
     io::{self, BufWriter, Write},
-    sync::{mpsc, Arc, Mutex},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, RwLock,
+    },
+    thread,
 };
 
 #[cfg(feature = "date-based")]
-use std::{
-    ffi::OsString,
-    fs::OpenOptions,
-    path::{Path, PathBuf},
+use std::{ffi::OsString, fs::OpenOptions, path::Path};
+
+#[cfg(any(feature = "date-based", all(target_os = "linux", feature = "journald")))]
+use std::path::PathBuf;
+
+#[cfg(all(target_os = "linux", feature = "journald"))]
+use std::os::{
+    raw::{c_char, c_int},
+    unix::{io::AsRawFd, net::UnixDatagram},
 };
 
 use log::{self, Log};
 
-use crate::{Filter, Formatter};
+use crate::{Filter, Formatter, MemoryBuffer, Multiline, Redactor};
+
+#[cfg(feature = "date-based")]
+use crate::time::Clock;
+
+#[cfg(feature = "kv")]
+use crate::FieldsFn;
 
 #[cfg(all(not(windows), feature = "syslog-4"))]
 use crate::{Syslog4Rfc3164Logger, Syslog4Rfc5424Logger, Syslog4TransformFn};
@@ -24,18 +41,46 @@ use crate::{Syslog6Rfc3164Logger, Syslog6Rfc5424Logger, Syslog6TransformFn};
 #[cfg(all(not(windows), feature = "syslog-7"))]
 use crate::{Syslog7Rfc3164Logger, Syslog7Rfc5424Logger, Syslog7TransformFn};
 
-pub enum LevelConfiguration {
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+use crate::{SyslogFacility, SyslogSeverity, SyslogSeverityFn};
+
+/// Per-target configuration, used for levels and anything else looked up by
+/// target module.
+pub enum LevelConfiguration<T = log::LevelFilter> {
     JustDefault,
-    Minimal(Vec<(Cow<'static, str>, log::LevelFilter)>),
-    Many(HashMap<Cow<'static, str>, log::LevelFilter>),
+    Minimal(Vec<(Cow<'static, str>, T)>),
+    Many(HashMap<Cow<'static, str>, T>),
 }
 
 pub struct Dispatch {
     pub output: Vec<Output>,
     pub default_level: log::LevelFilter,
     pub levels: LevelConfiguration,
-    pub format: Option<Box<Formatter>>,
+    pub format: Vec<Box<Formatter>>,
     pub filters: Vec<Box<Filter>>,
+    pub render_once: bool,
+    pub multiline: Option<Multiline>,
+    pub redactor: Option<Box<Redactor>>,
+    pub max_message_len: Option<usize>,
+    #[cfg(feature = "kv")]
+    pub fields: Option<Fields>,
+}
+
+/// Key-values added by `Dispatch::with_field` and `with_fields_from`.
+#[cfg(feature = "kv")]
+pub struct Fields {
+    /// Static fields, followed by computed ones once they've been computed.
+    resolved: RwLock<Arc<[Field]>>,
+    pending: Mutex<Vec<Box<FieldsFn>>>,
+    has_pending: AtomicBool,
 }
 
 /// Callback struct for use within a formatter closure
@@ -57,11 +102,19 @@ pub struct Dispatch {
 #[must_use = "format callback must be used for log to process correctly"]
 pub struct FormatCallback<'a>(InnerFormatCallback<'a>);
 
-struct InnerFormatCallback<'a>(&'a mut bool, &'a Dispatch, &'a log::Record<'a>);
+struct InnerFormatCallback<'a>(
+    &'a mut bool,
+    &'a Dispatch,
+    &'a log::Record<'a>,
+    // index of the next formatting stage
+    usize,
+);
 
 pub enum Output {
     Stdout(Stdout),
     Stderr(Stderr),
+    StdSplit(StdSplit),
+    MemoryBuffer(MemoryBuffer),
     File(File),
     Sender(Sender),
     #[cfg(all(not(windows), feature = "syslog-3"))]
@@ -78,7 +131,7 @@ pub enum Output {
     Syslog7Rfc3164(Syslog7Rfc3164),
     #[cfg(all(not(windows), feature = "syslog-7"))]
     Syslog7Rfc5424(Syslog7Rfc5424),
-    Dispatch(Dispatch),
+    Dispatch(Box<Dispatch>),
     SharedDispatch(Arc<Dispatch>),
     OtherBoxed(Box<dyn Log>),
     OtherStatic(&'static dyn Log),
@@ -90,6 +143,8 @@ pub enum Output {
     Reopen(Reopen),
     #[cfg(all(not(windows), feature = "reopen-1"))]
     Reopen1(Reopen1),
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    Journald(Journald),
 }
 
 pub struct Stdout {
@@ -102,6 +157,13 @@ pub struct Stderr {
     pub line_sep: Cow<'static, str>,
 }
 
+pub struct StdSplit {
+    pub stdout: io::Stdout,
+    pub stderr: io::Stderr,
+    pub threshold: log::Level,
+    pub line_sep: Cow<'static, str>,
+}
+
 pub struct File {
     pub stream: Mutex<BufWriter<fs::File>>,
     pub line_sep: Cow<'static, str>,
@@ -129,48 +191,248 @@ pub struct Reopen1 {
     pub line_sep: Cow<'static, str>,
 }
 
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+// syslog 4 can't change facilities, so they're unused with only it enabled.
+#[cfg_attr(
+    not(any(feature = "syslog-3", feature = "syslog-6", feature = "syslog-7")),
+    allow(dead_code)
+)]
+pub struct SyslogMapping {
+    pub severity: Option<Box<SyslogSeverityFn>>,
+    pub default_facility: Option<SyslogFacility>,
+    pub facilities: LevelConfiguration<SyslogFacility>,
+}
+
 #[cfg(all(not(windows), feature = "syslog-3"))]
 pub struct Syslog3 {
-    pub inner: syslog3::Logger,
+    pub inner: Mutex<syslog3::Logger>,
+    pub mapping: SyslogMapping,
 }
 
 #[cfg(all(not(windows), feature = "syslog-4"))]
 pub struct Syslog4Rfc3164 {
     pub inner: Mutex<Syslog4Rfc3164Logger>,
+    pub mapping: SyslogMapping,
 }
 
 #[cfg(all(not(windows), feature = "syslog-4"))]
 pub struct Syslog4Rfc5424 {
     pub inner: Mutex<Syslog4Rfc5424Logger>,
     pub transform: Box<Syslog4TransformFn>,
+    pub mapping: SyslogMapping,
 }
 
 #[cfg(all(not(windows), feature = "syslog-6"))]
 pub struct Syslog6Rfc3164 {
     pub inner: Mutex<Syslog6Rfc3164Logger>,
+    pub mapping: SyslogMapping,
+    pub default_facility: syslog6::Facility,
 }
 
 #[cfg(all(not(windows), feature = "syslog-6"))]
 pub struct Syslog6Rfc5424 {
     pub inner: Mutex<Syslog6Rfc5424Logger>,
     pub transform: Box<Syslog6TransformFn>,
+    pub mapping: SyslogMapping,
+    pub default_facility: syslog6::Facility,
 }
 
 #[cfg(all(not(windows), feature = "syslog-7"))]
 pub struct Syslog7Rfc3164 {
     pub inner: Mutex<Syslog7Rfc3164Logger>,
+    pub mapping: SyslogMapping,
+    pub default_facility: syslog7::Facility,
 }
 
 #[cfg(all(not(windows), feature = "syslog-7"))]
 pub struct Syslog7Rfc5424 {
     pub inner: Mutex<Syslog7Rfc5424Logger>,
     pub transform: Box<Syslog7TransformFn>,
+    pub mapping: SyslogMapping,
+    pub default_facility: syslog7::Facility,
 }
 
 pub struct Panic;
 
 pub struct Null;
 
+/// Capacity above which `RENDER_BUFFER` is shrunk after rendering, so that a
+/// single large message doesn't stay allocated.
+const MAX_RETAINED_RENDER_BUFFER: usize = 16 * 1024;
+
+/// A key-value added to records by `fern::context` or a dispatch.
+pub type Field = (Cow<'static, str>, String);
+
+thread_local! {
+    /// Key-values pushed through `fern::context`, outermost first.
+    ///
+    /// Records being logged share the list, rather than copying it, so it's
+    /// only copied when pushed to while a record is being logged.
+    pub static CONTEXT: RefCell<Option<Rc<Vec<Field>>>> = const { RefCell::new(None) };
+    /// Buffer for messages rendered by dispatches with `render_once` set.
+    static RENDER_BUFFER: Cell<String> = const { Cell::new(String::new()) };
+    /// Whether the output currently being logged to uses colors, if known.
+    #[cfg(feature = "colored")]
+    pub static OUTPUT_COLORS: Cell<Option<bool>> = const { Cell::new(None) };
+    /// Whether the context has been added to the record currently being logged.
+    #[cfg(feature = "kv")]
+    static CONTEXT_APPLIED: Cell<bool> = const { Cell::new(false) };
+    /// Address of the `Fields` whose functions are running on this thread.
+    #[cfg(feature = "kv")]
+    static COMPUTING_FIELDS: Cell<usize> = const { Cell::new(0) };
+    /// Whether a record is being logged through the `Closeable` on this thread.
+    static LOGGING_CLOSEABLE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the context as applied until dropped, even when panicking.
+#[cfg(feature = "kv")]
+struct ContextApplied;
+
+#[cfg(feature = "kv")]
+impl ContextApplied {
+    fn enter() -> Self {
+        CONTEXT_APPLIED.with(|applied| applied.set(true));
+        ContextApplied
+    }
+}
+
+#[cfg(feature = "kv")]
+impl Drop for ContextApplied {
+    fn drop(&mut self) {
+        // ignore errors - the thread is shutting down.
+        let _ = CONTEXT_APPLIED.try_with(|applied| applied.set(false));
+    }
+}
+
+/// Marks fields as being computed until dropped, even when panicking.
+#[cfg(feature = "kv")]
+struct ComputingFields {
+    previous: usize,
+}
+
+#[cfg(feature = "kv")]
+impl ComputingFields {
+    fn enter(fields: &Fields) -> Self {
+        let previous = COMPUTING_FIELDS.with(|current| current.replace(fields.address()));
+        ComputingFields { previous }
+    }
+}
+
+#[cfg(feature = "kv")]
+impl Drop for ComputingFields {
+    fn drop(&mut self) {
+        // ignore errors - the thread is shutting down.
+        let _ = COMPUTING_FIELDS.try_with(|current| current.set(self.previous));
+    }
+}
+
+/// Marks a record as being logged through the `Closeable` until dropped, even
+/// when panicking.
+struct LoggingCloseable {
+    previous: bool,
+}
+
+impl LoggingCloseable {
+    fn enter() -> Self {
+        let previous = LOGGING_CLOSEABLE.with(|logging| logging.replace(true));
+        LoggingCloseable { previous }
+    }
+}
+
+impl Drop for LoggingCloseable {
+    fn drop(&mut self) {
+        // ignore errors - the thread is shutting down.
+        let _ = LOGGING_CLOSEABLE.try_with(|logging| logging.set(self.previous));
+    }
+}
+
+/// Sets whether colors are used until dropped, even when panicking.
+#[cfg(feature = "colored")]
+struct OutputColors {
+    previous: Option<bool>,
+}
+
+#[cfg(feature = "colored")]
+impl OutputColors {
+    /// Sets `colors`, if known, keeping the current setting otherwise.
+    fn enter(colors: Option<bool>) -> Self {
+        let previous = OUTPUT_COLORS.with(|current| match colors {
+            Some(colors) => current.replace(Some(colors)),
+            None => current.get(),
+        });
+        OutputColors { previous }
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Drop for OutputColors {
+    fn drop(&mut self) {
+        // ignore errors - the thread is shutting down.
+        let _ = OUTPUT_COLORS.try_with(|current| current.set(self.previous));
+    }
+}
+
+/// A record's key-values, followed by the context's or a dispatch's fields.
+#[cfg(feature = "kv")]
+struct ExtendedSource<'a> {
+    record: &'a dyn log::kv::Source,
+    extra: &'a [Field],
+}
+
+#[cfg(feature = "kv")]
+impl log::kv::Source for ExtendedSource<'_> {
+    fn visit<'kvs>(
+        &'kvs self,
+        visitor: &mut dyn log::kv::VisitSource<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        self.record.visit(visitor)?;
+        // last first, skipping keys shadowed by later values or the record's
+        // own, as in `get`.
+        for (index, (key, value)) in self.extra.iter().enumerate().rev() {
+            if self.extra[index + 1..].iter().any(|(k, _)| k == key)
+                || self.record.get(log::kv::Key::from_str(key)).is_some()
+            {
+                continue;
+            }
+            visitor.visit_pair(log::kv::Key::from_str(key), log::kv::Value::from(&**value))?;
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: log::kv::Key) -> Option<log::kv::Value<'_>> {
+        self.record.get(key.clone()).or_else(|| {
+            self.extra
+                .iter()
+                .rev()
+                .find(|(k, _)| *k == key.as_str())
+                .map(|(_, value)| log::kv::Value::from(&**value))
+        })
+    }
+}
+
+pub struct Closeable {
+    pub closed: AtomicBool,
+    pub to_stderr: AtomicBool,
+    /// Only locked to clone or take the dispatch, so records logged while
+    /// logging a record never wait for `close`.
+    pub dispatch: RwLock<Option<Arc<Dispatch>>>,
+}
+
+#[cfg(all(target_os = "linux", feature = "journald"))]
+pub struct Journald {
+    pub socket: Option<UnixDatagram>,
+    pub socket_path: PathBuf,
+    pub syslog_identifier: Option<Cow<'static, str>>,
+}
+
 /// File logger with a dynamic time-based name.
 #[derive(Debug)]
 #[cfg(feature = "date-based")]
@@ -184,6 +446,8 @@ pub struct DateBased {
 pub enum ConfiguredTimezone {
     Local,
     Utc,
+    #[cfg(feature = "timezone")]
+    Zone(crate::time::Timezone),
 }
 
 #[derive(Debug)]
@@ -194,6 +458,7 @@ pub struct DateBasedConfig {
     pub file_prefix: PathBuf,
     pub file_suffix: Cow<'static, str>,
     pub timezone: ConfiguredTimezone,
+    pub clock: Arc<dyn Clock>,
 }
 
 #[derive(Debug)]
@@ -228,19 +493,35 @@ impl DateBasedConfig {
         file_prefix: PathBuf,
         file_suffix: Cow<'static, str>,
         timezone: ConfiguredTimezone,
+        clock: Arc<dyn Clock>,
     ) -> Self {
         DateBasedConfig {
             line_sep,
             file_prefix,
             file_suffix,
             timezone,
+            clock,
         }
     }
 
     pub fn compute_current_suffix(&self) -> String {
+        let now = self.clock.now();
         match self.timezone {
-            ConfiguredTimezone::Utc => chrono::Utc::now().format(&self.file_suffix).to_string(),
-            ConfiguredTimezone::Local => chrono::Local::now().format(&self.file_suffix).to_string(),
+            ConfiguredTimezone::Utc => chrono::DateTime::<chrono::Utc>::from(now)
+                .format(&self.file_suffix)
+                .to_string(),
+            ConfiguredTimezone::Local => chrono::DateTime::<chrono::Local>::from(now)
+                .format(&self.file_suffix)
+                .to_string(),
+            #[cfg(feature = "timezone")]
+            ConfiguredTimezone::Zone(ref timezone) => {
+                let offset = chrono::FixedOffset::east_opt(timezone.offset_at(now).seconds())
+                    .expect("UTC offsets are less than a day");
+                chrono::DateTime::<chrono::Utc>::from(now)
+                    .with_timezone(&offset)
+                    .format(&self.file_suffix)
+                    .to_string()
+            }
         }
     }
 
@@ -261,8 +542,8 @@ impl DateBasedConfig {
     }
 }
 
-impl From<Vec<(Cow<'static, str>, log::LevelFilter)>> for LevelConfiguration {
-    fn from(mut levels: Vec<(Cow<'static, str>, log::LevelFilter)>) -> Self {
+impl<T> From<Vec<(Cow<'static, str>, T)>> for LevelConfiguration<T> {
+    fn from(mut levels: Vec<(Cow<'static, str>, T)>) -> Self {
         // Benchmarked separately: https://gist.github.com/daboross/976978d8200caf86e02acb6805961195
         // Use Vec if there are fewer than 15 items, HashMap if there are more than 15.
         match levels.len() {
@@ -276,10 +557,10 @@ impl From<Vec<(Cow<'static, str>, log::LevelFilter)>> for LevelConfiguration {
     }
 }
 
-impl LevelConfiguration {
+impl<T: Copy> LevelConfiguration<T> {
     // inline since we use it literally once.
     #[inline]
-    fn find_module(&self, module: &str) -> Option<log::LevelFilter> {
+    fn find_module(&self, module: &str) -> Option<T> {
         match *self {
             LevelConfiguration::JustDefault => None,
             _ => {
@@ -313,7 +594,7 @@ impl LevelConfiguration {
         }
     }
 
-    fn find_exact(&self, module: &str) -> Option<log::LevelFilter> {
+    fn find_exact(&self, module: &str) -> Option<T> {
         match *self {
             LevelConfiguration::JustDefault => None,
             LevelConfiguration::Minimal(ref levels) => levels
@@ -325,11 +606,65 @@ impl LevelConfiguration {
     }
 }
 
+#[cfg(feature = "colored")]
+impl Output {
+    /// Whether colors should be written to this output, or `None` for
+    /// dispatches whose outputs disagree, to keep the parent's setting.
+    fn colors(&self, record: &log::Record) -> Option<bool> {
+        use crate::colors::{stderr_enabled, stdout_enabled};
+
+        match *self {
+            Output::Stdout(_) => Some(stdout_enabled()),
+            Output::Stderr(_) => Some(stderr_enabled()),
+            Output::StdSplit(ref s) if record.level() <= s.threshold => Some(stderr_enabled()),
+            Output::StdSplit(_) => Some(stdout_enabled()),
+            Output::Dispatch(ref s) => s.colors(record),
+            Output::SharedDispatch(ref s) => s.colors(record),
+            _ => Some(false),
+        }
+    }
+}
+
+#[cfg(feature = "colored")]
+impl Dispatch {
+    /// Whether colors should be written to all of this dispatch's outputs,
+    /// or `None` if they disagree.
+    fn colors(&self, record: &log::Record) -> Option<bool> {
+        let mut outputs = self.output.iter().map(|output| output.colors(record));
+        let first = outputs.next()??;
+        outputs.all(|colors| colors == Some(first)).then_some(first)
+    }
+}
+
+impl Output {
+    /// Logs a record whose message has already been rendered into `text`.
+    ///
+    /// Outputs writing the message as-is write `text` directly, rather than
+    /// formatting the record's arguments again.
+    fn log_rendered(&self, record: &log::Record, text: &str) {
+        match *self {
+            Output::Stdout(ref s) => s.log_rendered(record, text),
+            Output::Stderr(ref s) => s.log_rendered(record, text),
+            Output::StdSplit(ref s) => s.log_rendered(record, text),
+            Output::File(ref s) => s.log_rendered(record, text),
+            Output::Sender(ref s) => s.log_rendered(record, text),
+            Output::Writer(ref s) => s.log_rendered(record, text),
+            #[cfg(all(not(windows), feature = "reopen-03"))]
+            Output::Reopen(ref s) => s.log_rendered(record, text),
+            #[cfg(all(not(windows), feature = "reopen-1"))]
+            Output::Reopen1(ref s) => s.log_rendered(record, text),
+            _ => self.log(record),
+        }
+    }
+}
+
 impl Log for Output {
     fn enabled(&self, metadata: &log::Metadata) -> bool {
         match *self {
             Output::Stdout(ref s) => s.enabled(metadata),
             Output::Stderr(ref s) => s.enabled(metadata),
+            Output::StdSplit(ref s) => s.enabled(metadata),
+            Output::MemoryBuffer(ref s) => s.enabled(metadata),
             Output::File(ref s) => s.enabled(metadata),
             Output::Sender(ref s) => s.enabled(metadata),
             Output::Dispatch(ref s) => s.enabled(metadata),
@@ -358,13 +693,19 @@ impl Log for Output {
             Output::Reopen(ref s) => s.enabled(metadata),
             #[cfg(all(not(windows), feature = "reopen-1"))]
             Output::Reopen1(ref s) => s.enabled(metadata),
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            Output::Journald(ref s) => s.enabled(metadata),
         }
     }
 
     fn log(&self, record: &log::Record) {
+        #[cfg(feature = "colored")]
+        let _colors = OutputColors::enter(self.colors(record));
         match *self {
             Output::Stdout(ref s) => s.log(record),
             Output::Stderr(ref s) => s.log(record),
+            Output::StdSplit(ref s) => s.log(record),
+            Output::MemoryBuffer(ref s) => s.log(record),
             Output::File(ref s) => s.log(record),
             Output::Sender(ref s) => s.log(record),
             Output::Dispatch(ref s) => s.log(record),
@@ -393,6 +734,8 @@ impl Log for Output {
             Output::Reopen(ref s) => s.log(record),
             #[cfg(all(not(windows), feature = "reopen-1"))]
             Output::Reopen1(ref s) => s.log(record),
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            Output::Journald(ref s) => s.log(record),
         }
     }
 
@@ -400,6 +743,8 @@ impl Log for Output {
         match *self {
             Output::Stdout(ref s) => s.flush(),
             Output::Stderr(ref s) => s.flush(),
+            Output::StdSplit(ref s) => s.flush(),
+            Output::MemoryBuffer(ref s) => s.flush(),
             Output::File(ref s) => s.flush(),
             Output::Sender(ref s) => s.flush(),
             Output::Dispatch(ref s) => s.flush(),
@@ -428,10 +773,128 @@ impl Log for Output {
             Output::Reopen(ref s) => s.flush(),
             #[cfg(all(not(windows), feature = "reopen-1"))]
             Output::Reopen1(ref s) => s.flush(),
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            Output::Journald(ref s) => s.flush(),
+        }
+    }
+}
+
+impl Log for Closeable {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        match self.dispatch() {
+            Some(dispatch) => dispatch.enabled(metadata),
+            None => self.to_stderr.load(Ordering::SeqCst),
+        }
+    }
+
+    fn log(&self, record: &log::Record) {
+        match self.dispatch() {
+            Some(dispatch) => {
+                let _logging = LoggingCloseable::enter();
+                dispatch.log(record);
+            }
+            None => {
+                if self.to_stderr.load(Ordering::SeqCst) {
+                    let _ = writeln!(io::stderr(), "{}", record.args());
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(dispatch) = self.dispatch() {
+            dispatch.flush();
         }
     }
 }
 
+impl Closeable {
+    /// Returns the dispatch, unless closed.
+    fn dispatch(&self) -> Option<Arc<Dispatch>> {
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        self.dispatch
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Closes the dispatch, returning `false` if it was already closed, in
+    /// which case the mode it was first closed with is kept.
+    pub fn close(&self, to_stderr: bool) -> bool {
+        let dispatch = {
+            let mut dispatch = self.dispatch.write().unwrap_or_else(|e| e.into_inner());
+            if self.closed.load(Ordering::SeqCst) {
+                return false;
+            }
+            self.to_stderr.store(to_stderr, Ordering::SeqCst);
+            self.closed.store(true, Ordering::SeqCst);
+            dispatch.take()
+        };
+        if let Some(dispatch) = dispatch {
+            // wait for records being logged on other threads to finish. when
+            // closing while logging a record on this thread, that record
+            // holds the dispatch, and drops it last instead.
+            let logging = LOGGING_CLOSEABLE
+                .try_with(|logging| logging.get())
+                .unwrap_or(false);
+            while !logging && Arc::strong_count(&dispatch) > 1 {
+                thread::yield_now();
+            }
+            dispatch.flush();
+            // dropping the last reference closes files and sockets.
+            drop(dispatch);
+        }
+        true
+    }
+}
+
+#[cfg(feature = "kv")]
+impl Fields {
+    pub fn new(fields: Vec<Field>, fields_from: Vec<Box<FieldsFn>>) -> Option<Self> {
+        if fields.is_empty() && fields_from.is_empty() {
+            return None;
+        }
+        Some(Fields {
+            resolved: RwLock::new(fields.into()),
+            has_pending: AtomicBool::new(!fields_from.is_empty()),
+            pending: Mutex::new(fields_from),
+        })
+    }
+
+    fn get(&self) -> Arc<[Field]> {
+        // records logged by the functions themselves don't wait for the lock
+        // they're holding, and go without the computed fields.
+        let computing = COMPUTING_FIELDS
+            .try_with(|current| current.get() == self.address())
+            .unwrap_or(false);
+        if self.has_pending.load(Ordering::SeqCst) && !computing {
+            // hold the lock while computing, so other threads wait for the
+            // computed fields rather than logging without them.
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            if !pending.is_empty() {
+                let computed: Vec<_> = {
+                    let _computing = ComputingFields::enter(self);
+                    pending.iter().flat_map(|f| f()).collect()
+                };
+                let mut resolved = self.resolved.write().unwrap_or_else(|e| e.into_inner());
+                *resolved = resolved.iter().cloned().chain(computed).collect();
+                pending.clear();
+                self.has_pending.store(false, Ordering::SeqCst);
+            }
+        }
+        self.resolved
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn address(&self) -> usize {
+        self as *const Fields as usize
+    }
+}
+
 impl Log for Null {
     fn enabled(&self, _: &log::Metadata) -> bool {
         false
@@ -449,30 +912,17 @@ impl Log for Dispatch {
 
     fn log(&self, record: &log::Record) {
         if self.shallow_enabled(record.metadata()) {
-            match self.format {
-                Some(ref format) => {
-                    // flag to ensure the log message is completed even if the formatter doesn't
-                    // complete the callback.
-                    let mut callback_called_flag = false;
-
-                    (format)(
-                        FormatCallback(InnerFormatCallback(
-                            &mut callback_called_flag,
-                            self,
-                            record,
-                        )),
-                        record.args(),
-                        record,
-                    );
-
-                    if !callback_called_flag {
-                        self.finish_logging(record);
-                    }
-                }
-                None => {
-                    self.finish_logging(record);
-                }
-            }
+            // decided before formatting, for messages rendered before
+            // reaching the outputs. without a decision, there are no colors.
+            #[cfg(feature = "colored")]
+            let _colors =
+                OutputColors::enter(Some(self.colors(record).unwrap_or_else(|| {
+                    OUTPUT_COLORS.with(|current| current.get().unwrap_or(false))
+                })));
+            #[cfg(feature = "kv")]
+            self.log_with_context(record);
+            #[cfg(not(feature = "kv"))]
+            self.format_and_log(record);
         }
     }
 
@@ -484,12 +934,242 @@ impl Log for Dispatch {
 }
 
 impl Dispatch {
+    #[cfg(feature = "kv")]
+    fn log_with_context(&self, record: &log::Record) {
+        // only the outermost dispatch adds the context, so that it isn't
+        // repeated when passing through nested dispatches.
+        if !CONTEXT_APPLIED.with(Cell::get) {
+            let context = CONTEXT.with(|context| context.borrow().clone());
+            if let Some(context) = context.filter(|context| !context.is_empty()) {
+                let _applied = ContextApplied::enter();
+                let source = ExtendedSource {
+                    record: record.key_values(),
+                    extra: &context,
+                };
+                self.log_with_fields(&record.to_builder().key_values(&source).build());
+                return;
+            }
+        }
+        self.log_with_fields(record);
+    }
+
+    #[cfg(feature = "kv")]
+    fn log_with_fields(&self, record: &log::Record) {
+        match self.fields {
+            Some(ref fields) => {
+                let fields = fields.get();
+                let source = ExtendedSource {
+                    record: record.key_values(),
+                    extra: &fields,
+                };
+                self.format_and_log(&record.to_builder().key_values(&source).build());
+            }
+            None => self.format_and_log(record),
+        }
+    }
+
+    fn format_and_log(&self, record: &log::Record) {
+        match self.multiline {
+            Some(ref policy) => self.format_multiline(policy, record),
+            None => self.format_from(0, record),
+        }
+    }
+
+    fn format_multiline(&self, policy: &Multiline, record: &log::Record) {
+        use std::fmt::Write;
+
+        // taken out of the thread-local, as in `render_and_log`.
+        let mut buffer = RENDER_BUFFER.with(|buffer| buffer.take());
+        if write!(buffer, "{}", record.args()).is_ok() {
+            self.format_lines(policy, record, &buffer);
+        } else {
+            // let each output report the error.
+            self.format_from(0, record);
+        }
+
+        buffer.clear();
+        buffer.shrink_to(MAX_RETAINED_RENDER_BUFFER);
+        // ignore errors - the thread is shutting down.
+        let _ = RENDER_BUFFER.try_with(|cell| cell.replace(buffer));
+    }
+
+    fn format_lines(&self, policy: &Multiline, record: &log::Record, message: &str) {
+        let message = message
+            .strip_suffix('\n')
+            .map_or(message, |m| m.strip_suffix('\r').unwrap_or(m));
+
+        match *policy {
+            // escaping also doubles backslashes, so single lines need it too.
+            Multiline::Escape => self.format_from(
+                0,
+                &with_args(record, format_args!("{}", EscapedNewlines(message))),
+            ),
+            _ if !message.contains(['\n', '\r']) => {
+                self.format_from(0, &with_args(record, format_args!("{}", message)));
+            }
+            Multiline::Indent(ref prefix) => self.format_from(
+                0,
+                &with_args(record, format_args!("{}", Indented(message, prefix))),
+            ),
+            Multiline::RepeatHeader => {
+                for line in message.split('\n') {
+                    let line = line.strip_suffix('\r').unwrap_or(line);
+                    self.format_from(0, &with_args(record, format_args!("{}", line)));
+                }
+            }
+        }
+    }
+
+    /// Runs the formatting stages starting at `stage`, then logs to all
+    /// outputs.
+    fn format_from(&self, stage: usize, record: &log::Record) {
+        match self.format.get(stage) {
+            Some(format) => {
+                // flag to ensure the log message is completed even if the formatter doesn't
+                // complete the callback.
+                let mut callback_called_flag = false;
+
+                (format)(
+                    FormatCallback(InnerFormatCallback(
+                        &mut callback_called_flag,
+                        self,
+                        record,
+                        stage + 1,
+                    )),
+                    record.args(),
+                    record,
+                );
+
+                if !callback_called_flag {
+                    self.format_from(stage + 1, record);
+                }
+            }
+            None => {
+                self.finish_logging(record);
+            }
+        }
+    }
+
     fn finish_logging(&self, record: &log::Record) {
+        // redact before truncating, so secrets cut short are still found.
+        match self.redactor {
+            Some(ref redactor) => self.redact_and_log(redactor, record),
+            None => match self.max_message_len {
+                Some(max_len) => self.truncate_and_log(max_len, record),
+                None if self.render_once => self.render_and_log(record),
+                None => self.log_to_outputs(record),
+            },
+        }
+    }
+
+    /// Passes on a record whose message has already been rendered, truncating
+    /// it first if needed.
+    fn log_rendered(&self, record: &log::Record) {
+        match self.max_message_len {
+            Some(max_len) => self.truncate_and_log(max_len, record),
+            None => self.log_to_outputs(record),
+        }
+    }
+
+    fn truncate_and_log(&self, max_len: usize, record: &log::Record) {
+        use std::fmt::Write;
+
+        // taken out of the thread-local, as in `render_and_log`.
+        let mut buffer = RENDER_BUFFER.with(|buffer| buffer.take());
+        let mut truncated = Truncated {
+            kept: &mut buffer,
+            max_len,
+            dropped: 0,
+        };
+        if write!(truncated, "{}", record.args()).is_err() {
+            // let each output report the error.
+            self.log_to_outputs(record);
+        } else if truncated.dropped == 0 {
+            self.log_to_outputs(&with_args(record, format_args!("{}", truncated.kept)));
+        } else {
+            self.log_to_outputs(&with_args(
+                record,
+                format_args!(
+                    "{}\u{2026}[truncated {} bytes]",
+                    truncated.kept, truncated.dropped
+                ),
+            ));
+        }
+
+        buffer.clear();
+        buffer.shrink_to(MAX_RETAINED_RENDER_BUFFER);
+        // ignore errors - the thread is shutting down.
+        let _ = RENDER_BUFFER.try_with(|cell| cell.replace(buffer));
+    }
+
+    fn redact_and_log(&self, redactor: &Redactor, record: &log::Record) {
+        let message = record.args().to_string();
+        let message = redactor.redact(&message);
+
+        #[cfg(feature = "kv")]
+        {
+            struct Redact<'a>(&'a Redactor, Vec<(String, String)>);
+
+            impl<'kvs> log::kv::VisitSource<'kvs> for Redact<'_> {
+                fn visit_pair(
+                    &mut self,
+                    key: log::kv::Key<'kvs>,
+                    value: log::kv::Value<'kvs>,
+                ) -> Result<(), log::kv::Error> {
+                    let value = value.to_string();
+                    let value = self.0.redact_value(key.as_str(), &value).into_owned();
+                    self.1.push((key.to_string(), value));
+                    Ok(())
+                }
+            }
+
+            let mut redacted = Redact(redactor, Vec::new());
+            // ignore errors - only the key-values visited so far are kept.
+            let _ = record.key_values().visit(&mut redacted);
+            let key_values = redacted.1;
+            self.log_rendered(
+                &with_args(record, format_args!("{}", message))
+                    .to_builder()
+                    .key_values(&key_values)
+                    .build(),
+            );
+        }
+        #[cfg(not(feature = "kv"))]
+        self.log_rendered(&with_args(record, format_args!("{}", message)));
+    }
+
+    fn render_and_log(&self, record: &log::Record) {
+        use std::fmt::Write;
+
+        // taken out of the thread-local, so that messages logged while
+        // rendering or logging use their own buffer.
+        let mut buffer = RENDER_BUFFER.with(|buffer| buffer.take());
+        if write!(buffer, "{}", record.args()).is_ok() {
+            self.log_text_to_outputs(&with_args(record, format_args!("{}", buffer)), &buffer);
+        } else {
+            // let each output report the error.
+            self.log_to_outputs(record);
+        }
+
+        buffer.clear();
+        buffer.shrink_to(MAX_RETAINED_RENDER_BUFFER);
+        // ignore errors - the thread is shutting down.
+        let _ = RENDER_BUFFER.try_with(|cell| cell.replace(buffer));
+    }
+
+    fn log_to_outputs(&self, record: &log::Record) {
         for log in &self.output {
             log.log(record);
         }
     }
 
+    /// Logs a record whose message has already been rendered into `text`.
+    fn log_text_to_outputs(&self, record: &log::Record, text: &str) {
+        for log in &self.output {
+            log.log_rendered(record, text);
+        }
+    }
+
     /// Check whether this log's filters prevent the given log from happening.
     fn shallow_enabled(&self, metadata: &log::Metadata) -> bool {
         metadata.level()
@@ -529,27 +1209,173 @@ impl FormatCallback<'_> {
     ///
     /// [`format_args!`]: https://doc.rust-lang.org/std/macro.format_args.html
     pub fn finish(self, formatted_message: fmt::Arguments) {
-        let FormatCallback(InnerFormatCallback(callback_called_flag, dispatch, record)) = self;
+        let FormatCallback(InnerFormatCallback(callback_called_flag, dispatch, record, next_stage)) =
+            self;
 
         // let the dispatch know that we did in fact get called.
         *callback_called_flag = true;
 
-        // NOTE: This needs to be updated whenever new things are added to
-        // `log::Record`.
-        let new_record = log::RecordBuilder::new()
-            .args(formatted_message)
-            .metadata(record.metadata().clone())
-            .level(record.level())
-            .target(record.target())
-            .module_path(record.module_path())
-            .file(record.file())
-            .line(record.line())
-            .build();
+        dispatch.format_from(next_stage, &with_args(record, formatted_message));
+    }
+}
+
+/// Writer keeping at most `max_len` bytes, and counting the rest.
+struct Truncated<'a> {
+    kept: &'a mut String,
+    max_len: usize,
+    dropped: usize,
+}
+
+impl fmt::Write for Truncated<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.dropped > 0 {
+            self.dropped += s.len();
+            return Ok(());
+        }
+        let room = self.max_len - self.kept.len();
+        if s.len() <= room {
+            self.kept.push_str(s);
+        } else {
+            let mut end = room;
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+            self.kept.push_str(&s[..end]);
+            self.dropped = s.len() - end;
+        }
+        Ok(())
+    }
+}
+
+/// A message with each line after the first prefixed.
+struct Indented<'a>(&'a str, &'a str);
 
-        dispatch.finish_logging(&new_record);
+impl fmt::Display for Indented<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, line) in self.0.split('\n').enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if index > 0 {
+                write!(f, "\n{}", self.1)?;
+            }
+            f.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A message with newlines and carriage returns escaped, and backslashes
+/// doubled so the escaped text can be told apart from the original.
+struct EscapedNewlines<'a>(&'a str);
+
+impl fmt::Display for EscapedNewlines<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut rest = self.0;
+        while let Some(index) = rest.find(['\n', '\r', '\\']) {
+            f.write_str(&rest[..index])?;
+            f.write_str(match rest.as_bytes()[index] {
+                b'\n' => "\\n",
+                b'\r' => "\\r",
+                _ => "\\\\",
+            })?;
+            rest = &rest[index + 1..];
+        }
+        f.write_str(rest)
+    }
+}
+
+/// A message with ANSI CSI escape sequences, such as colors, removed.
+pub struct StripAnsi<T>(pub T);
+
+impl<T: fmt::Display> fmt::Display for StripAnsi<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use std::fmt::Write;
+
+        let mut stripper = AnsiStripper {
+            f,
+            state: AnsiState::Text,
+        };
+        write!(stripper, "{}", self.0)?;
+        if let AnsiState::Escape = stripper.state {
+            // a trailing escape which isn't part of a sequence.
+            stripper.f.write_str("\x1B")?;
+        }
+        Ok(())
+    }
+}
+
+enum AnsiState {
+    Text,
+    /// After an escape character.
+    Escape,
+    /// Within a CSI sequence, until its final byte.
+    Sequence,
+}
+
+/// Writer removing CSI sequences, which may be split across writes.
+struct AnsiStripper<'a, 'f> {
+    f: &'a mut fmt::Formatter<'f>,
+    state: AnsiState,
+}
+
+impl fmt::Write for AnsiStripper<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        const ESCAPE: u8 = 0x1B;
+
+        // start of the text to copy, while in the `Text` state.
+        let mut copy_from = 0;
+        for (index, &byte) in s.as_bytes().iter().enumerate() {
+            match self.state {
+                AnsiState::Text => {
+                    if byte == ESCAPE {
+                        self.f.write_str(&s[copy_from..index])?;
+                        self.state = AnsiState::Escape;
+                    }
+                }
+                AnsiState::Escape => {
+                    if byte == b'[' {
+                        self.state = AnsiState::Sequence;
+                    } else {
+                        // not a CSI sequence - keep the escape.
+                        self.f.write_str("\x1B")?;
+                        if byte != ESCAPE {
+                            self.state = AnsiState::Text;
+                            copy_from = index;
+                        }
+                    }
+                }
+                AnsiState::Sequence => {
+                    if (0x40..=0x7E).contains(&byte) {
+                        self.state = AnsiState::Text;
+                        copy_from = index + 1;
+                    }
+                }
+            }
+        }
+        if let AnsiState::Text = self.state {
+            self.f.write_str(&s[copy_from..])?;
+        }
+        Ok(())
     }
 }
 
+/// Copies a record, replacing its message.
+fn with_args<'a>(record: &'a log::Record, args: fmt::Arguments<'a>) -> log::Record<'a> {
+    // NOTE: This needs to be updated whenever new things are added to
+    // `log::Record`.
+    let mut builder = log::RecordBuilder::new();
+    builder
+        .args(args)
+        .metadata(record.metadata().clone())
+        .level(record.level())
+        .target(record.target())
+        .module_path(record.module_path())
+        .file(record.file())
+        .line(record.line());
+    #[cfg(feature = "kv")]
+    builder.key_values(record.key_values());
+    builder.build()
+}
+
 // No need to write this twice (used for Stdout and Stderr structs)
 macro_rules! std_log_impl {
     ($ident:ident) => {
@@ -586,6 +1412,86 @@ macro_rules! std_log_impl {
 std_log_impl!(Stdout);
 std_log_impl!(Stderr);
 
+macro_rules! std_log_rendered_impl {
+    ($ident:ident) => {
+        impl $ident {
+            fn log_rendered(&self, record: &log::Record, text: &str) {
+                fallback_on_error(record, |_| {
+                    let mut stream = self.stream.lock();
+                    stream.write_all(text.as_bytes())?;
+                    stream.write_all(self.line_sep.as_bytes())?;
+                    Ok(())
+                });
+            }
+        }
+    };
+}
+
+std_log_rendered_impl!(Stdout);
+std_log_rendered_impl!(Stderr);
+
+impl Log for StdSplit {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        fallback_on_error(record, |record| {
+            // Formatting first prevents stuttering when the process of formatting
+            // itself is logged. See std_log_impl.
+            #[cfg(feature = "meta-logging-in-format")]
+            let msg = format!("{}{}", record.args(), self.line_sep);
+
+            // The stdout lock is held for both streams, so that records going
+            // to stderr can't be written in the middle of one going to stdout.
+            let mut stdout = self.stdout.lock();
+
+            if record.level() <= self.threshold {
+                let mut stderr = self.stderr.lock();
+                #[cfg(feature = "meta-logging-in-format")]
+                write!(stderr, "{}", msg)?;
+                #[cfg(not(feature = "meta-logging-in-format"))]
+                write!(stderr, "{}{}", record.args(), self.line_sep)?;
+            } else {
+                #[cfg(feature = "meta-logging-in-format")]
+                write!(stdout, "{}", msg)?;
+                #[cfg(not(feature = "meta-logging-in-format"))]
+                write!(stdout, "{}{}", record.args(), self.line_sep)?;
+                // keep stdout and stderr output in the order it was logged.
+                stdout.flush()?;
+            }
+
+            Ok(())
+        });
+    }
+
+    fn flush(&self) {
+        let _ = self.stdout.lock().flush();
+        let _ = self.stderr.lock().flush();
+    }
+}
+
+impl StdSplit {
+    fn log_rendered(&self, record: &log::Record, text: &str) {
+        fallback_on_error(record, |record| {
+            // see `log` for why the stdout lock is held for both streams.
+            let mut stdout = self.stdout.lock();
+
+            if record.level() <= self.threshold {
+                let mut stderr = self.stderr.lock();
+                stderr.write_all(text.as_bytes())?;
+                stderr.write_all(self.line_sep.as_bytes())?;
+            } else {
+                stdout.write_all(text.as_bytes())?;
+                stdout.write_all(self.line_sep.as_bytes())?;
+                stdout.flush()?;
+            }
+
+            Ok(())
+        });
+    }
+}
+
 macro_rules! writer_log_impl {
     ($ident:ident) => {
         impl Log for $ident {
@@ -626,6 +1532,18 @@ macro_rules! writer_log_impl {
                     .flush();
             }
         }
+
+        impl $ident {
+            fn log_rendered(&self, record: &log::Record, text: &str) {
+                fallback_on_error(record, |_| {
+                    let mut writer = self.stream.lock().unwrap_or_else(|e| e.into_inner());
+                    writer.write_all(text.as_bytes())?;
+                    writer.write_all(self.line_sep.as_bytes())?;
+                    writer.flush()?;
+                    Ok(())
+                });
+            }
+        }
     };
 }
 
@@ -657,6 +1575,21 @@ impl Log for Sender {
     fn flush(&self) {}
 }
 
+impl Sender {
+    fn log_rendered(&self, record: &log::Record, text: &str) {
+        fallback_on_error(record, |_| {
+            let mut msg = String::with_capacity(text.len() + self.line_sep.len());
+            msg.push_str(text);
+            msg.push_str(&self.line_sep);
+            self.stream
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .send(msg)?;
+            Ok(())
+        });
+    }
+}
+
 #[cfg(all(
     not(windows),
     any(
@@ -667,13 +1600,74 @@ impl Log for Sender {
     )
 ))]
 macro_rules! send_syslog {
-    ($logger:expr, $level:expr, $message:expr) => {
-        use log::Level;
-        match $level {
-            Level::Error => $logger.err($message)?,
-            Level::Warn => $logger.warning($message)?,
-            Level::Info => $logger.info($message)?,
-            Level::Debug | Level::Trace => $logger.debug($message)?,
+    ($logger:expr, $severity:expr, $message:expr) => {
+        match $severity {
+            SyslogSeverity::Emergency => $logger.emerg($message)?,
+            SyslogSeverity::Alert => $logger.alert($message)?,
+            SyslogSeverity::Critical => $logger.crit($message)?,
+            SyslogSeverity::Error => $logger.err($message)?,
+            SyslogSeverity::Warning => $logger.warning($message)?,
+            SyslogSeverity::Notice => $logger.notice($message)?,
+            SyslogSeverity::Informational => $logger.info($message)?,
+            SyslogSeverity::Debug => $logger.debug($message)?,
+        }
+    };
+}
+
+#[cfg(all(
+    not(windows),
+    any(
+        feature = "syslog-3",
+        feature = "syslog-4",
+        feature = "syslog-6",
+        feature = "syslog-7"
+    )
+))]
+impl SyslogMapping {
+    fn severity(&self, record: &log::Record) -> SyslogSeverity {
+        match self.severity {
+            Some(ref severity) => severity(record),
+            None => record.level().into(),
+        }
+    }
+
+    #[cfg(any(feature = "syslog-3", feature = "syslog-6", feature = "syslog-7"))]
+    fn facility(&self, target: &str) -> Option<SyslogFacility> {
+        self.facilities
+            .find_module(target)
+            .or(self.default_facility)
+    }
+}
+
+// syslog's `Facility` is a different type in each version, so this converts
+// our facility into whichever one is needed.
+#[cfg(all(
+    not(windows),
+    any(feature = "syslog-3", feature = "syslog-6", feature = "syslog-7")
+))]
+macro_rules! syslog_facility {
+    ($krate:ident, $facility:expr) => {
+        match $facility {
+            SyslogFacility::Kern => $krate::Facility::LOG_KERN,
+            SyslogFacility::User => $krate::Facility::LOG_USER,
+            SyslogFacility::Mail => $krate::Facility::LOG_MAIL,
+            SyslogFacility::Daemon => $krate::Facility::LOG_DAEMON,
+            SyslogFacility::Auth => $krate::Facility::LOG_AUTH,
+            SyslogFacility::Syslog => $krate::Facility::LOG_SYSLOG,
+            SyslogFacility::Lpr => $krate::Facility::LOG_LPR,
+            SyslogFacility::News => $krate::Facility::LOG_NEWS,
+            SyslogFacility::Uucp => $krate::Facility::LOG_UUCP,
+            SyslogFacility::Cron => $krate::Facility::LOG_CRON,
+            SyslogFacility::AuthPriv => $krate::Facility::LOG_AUTHPRIV,
+            SyslogFacility::Ftp => $krate::Facility::LOG_FTP,
+            SyslogFacility::Local0 => $krate::Facility::LOG_LOCAL0,
+            SyslogFacility::Local1 => $krate::Facility::LOG_LOCAL1,
+            SyslogFacility::Local2 => $krate::Facility::LOG_LOCAL2,
+            SyslogFacility::Local3 => $krate::Facility::LOG_LOCAL3,
+            SyslogFacility::Local4 => $krate::Facility::LOG_LOCAL4,
+            SyslogFacility::Local5 => $krate::Facility::LOG_LOCAL5,
+            SyslogFacility::Local6 => $krate::Facility::LOG_LOCAL6,
+            SyslogFacility::Local7 => $krate::Facility::LOG_LOCAL7,
         }
     };
 }
@@ -687,7 +1681,11 @@ impl Log for Syslog3 {
     fn log(&self, record: &log::Record) {
         fallback_on_error(record, |record| {
             let message = record.args();
-            send_syslog!(self.inner, record.level(), message);
+            let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(facility) = self.mapping.facility(record.target()) {
+                log.set_facility(syslog_facility!(syslog3, facility));
+            }
+            send_syslog!(log, self.mapping.severity(record), message);
 
             Ok(())
         });
@@ -705,7 +1703,7 @@ impl Log for Syslog4Rfc3164 {
         fallback_on_error(record, |record| {
             let message = record.args().to_string();
             let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-            send_syslog!(log, record.level(), message);
+            send_syslog!(log, self.mapping.severity(record), message);
 
             Ok(())
         });
@@ -723,7 +1721,7 @@ impl Log for Syslog4Rfc5424 {
         fallback_on_error(record, |record| {
             let transformed = (self.transform)(record);
             let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-            send_syslog!(log, record.level(), transformed);
+            send_syslog!(log, self.mapping.severity(record), transformed);
 
             Ok(())
         });
@@ -741,7 +1739,11 @@ impl Log for Syslog6Rfc3164 {
         fallback_on_error(record, |record| {
             let message = record.args().to_string();
             let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-            send_syslog!(log, record.level(), message);
+            log.formatter.facility = match self.mapping.facility(record.target()) {
+                Some(facility) => syslog_facility!(syslog6, facility),
+                None => self.default_facility,
+            };
+            send_syslog!(log, self.mapping.severity(record), message);
 
             Ok(())
         });
@@ -759,7 +1761,11 @@ impl Log for Syslog6Rfc5424 {
         fallback_on_error(record, |record| {
             let transformed = (self.transform)(record);
             let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-            send_syslog!(log, record.level(), transformed);
+            log.formatter.facility = match self.mapping.facility(record.target()) {
+                Some(facility) => syslog_facility!(syslog6, facility),
+                None => self.default_facility,
+            };
+            send_syslog!(log, self.mapping.severity(record), transformed);
 
             Ok(())
         });
@@ -777,7 +1783,11 @@ impl Log for Syslog7Rfc3164 {
         fallback_on_error(record, |record| {
             let message = record.args().to_string();
             let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-            send_syslog!(log, record.level(), message);
+            log.formatter.facility = match self.mapping.facility(record.target()) {
+                Some(facility) => syslog_facility!(syslog7, facility),
+                None => self.default_facility,
+            };
+            send_syslog!(log, self.mapping.severity(record), message);
 
             Ok(())
         });
@@ -795,7 +1805,11 @@ impl Log for Syslog7Rfc5424 {
         fallback_on_error(record, |record| {
             let transformed = (self.transform)(record);
             let mut log = self.inner.lock().unwrap_or_else(|e| e.into_inner());
-            send_syslog!(log, record.level(), transformed);
+            log.formatter.facility = match self.mapping.facility(record.target()) {
+                Some(facility) => syslog_facility!(syslog7, facility),
+                None => self.default_facility,
+            };
+            send_syslog!(log, self.mapping.severity(record), transformed);
 
             Ok(())
         });
@@ -870,6 +1884,229 @@ impl Log for DateBased {
     }
 }
 
+#[cfg(all(target_os = "linux", feature = "journald"))]
+impl Log for Journald {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        fallback_on_error(record, |record| {
+            let mut payload = Vec::with_capacity(256);
+            self.write_fields(&mut payload, record)?;
+            self.send(&payload)?;
+
+            Ok(())
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(all(target_os = "linux", feature = "journald"))]
+impl Journald {
+    fn write_fields(&self, payload: &mut Vec<u8>, record: &log::Record) -> io::Result<()> {
+        let severity = crate::SyslogSeverity::from(record.level());
+
+        write_journal_field(payload, "MESSAGE", record.args())?;
+        write_journal_field(payload, "PRIORITY", severity.code())?;
+        write_journal_field(payload, "TARGET", record.target())?;
+        if let Some(file) = record.file() {
+            write_journal_field(payload, "CODE_FILE", file)?;
+        }
+        if let Some(line) = record.line() {
+            write_journal_field(payload, "CODE_LINE", line)?;
+        }
+        if let Some(module_path) = record.module_path() {
+            write_journal_field(payload, "CODE_MODULE", module_path)?;
+        }
+        let current = thread::current();
+        match current.name() {
+            Some(name) => write_journal_field(payload, "THREAD", name)?,
+            None => write_journal_field(payload, "THREAD", format_args!("{:?}", current.id()))?,
+        }
+        if let Some(ref identifier) = self.syslog_identifier {
+            write_journal_field(payload, "SYSLOG_IDENTIFIER", identifier)?;
+        }
+
+        struct FieldVisitor<'a> {
+            payload: &'a mut Vec<u8>,
+            name: String,
+        }
+
+        impl<'kvs> log::kv::VisitSource<'kvs> for FieldVisitor<'_> {
+            fn visit_pair(
+                &mut self,
+                key: log::kv::Key<'kvs>,
+                value: log::kv::Value<'kvs>,
+            ) -> Result<(), log::kv::Error> {
+                journal_field_name(&mut self.name, key.as_str());
+                if !self.name.is_empty() {
+                    write_journal_field(self.payload, &self.name, value)?;
+                }
+                Ok(())
+            }
+        }
+
+        // key-values have no way of failing besides io errors from writing
+        // into the Vec, which can't happen.
+        let _ = record.key_values().visit(&mut FieldVisitor {
+            payload,
+            name: String::new(),
+        });
+
+        Ok(())
+    }
+
+    fn send(&self, payload: &[u8]) -> io::Result<()> {
+        let result = match self.socket {
+            Some(ref socket) => socket.send_to(payload, &self.socket_path),
+            None => UnixDatagram::unbound()?.send_to(payload, &self.socket_path),
+        };
+        match result {
+            Ok(_) => Ok(()),
+            // the payload is too large for a datagram, so pass it over in a
+            // memfd instead.
+            Err(ref e)
+                if e.raw_os_error() == Some(libc::EMSGSIZE)
+                    || e.raw_os_error() == Some(libc::ENOBUFS) =>
+            {
+                self.send_memfd(payload)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn send_memfd(&self, payload: &[u8]) -> io::Result<()> {
+        use std::os::unix::io::FromRawFd;
+
+        let fd = unsafe {
+            libc::memfd_create(
+                b"fern-journald\0".as_ptr() as *const c_char,
+                libc::MFD_ALLOW_SEALING | libc::MFD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Owns the fd, so that it's closed whether or not sending succeeds.
+        let mut memfd = unsafe { fs::File::from_raw_fd(fd) };
+        memfd.write_all(payload)?;
+
+        // journald only accepts memfds which are sealed against modification.
+        let seals =
+            libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL;
+        if unsafe { libc::fcntl(fd, libc::F_ADD_SEALS, seals) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&self.socket_path)?;
+
+        // u64s so that the buffer is aligned for a cmsghdr. One fd needs at
+        // most 24 bytes of control data.
+        let mut cmsg_buf = [0u64; 4];
+        let fd_size = std::mem::size_of::<c_int>() as u32;
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_control = cmsg_buf.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = unsafe { libc::CMSG_SPACE(fd_size) } as _;
+
+        unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(fd_size) as _;
+            std::ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut c_int, fd);
+        }
+
+        if unsafe { libc::sendmsg(socket.as_raw_fd(), &msg, libc::MSG_NOSIGNAL) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes one field in the journal's native protocol format.
+///
+/// Values without newlines are written as `NAME=value\n`. Otherwise, they're
+/// written as `NAME\n`, the value's length as a little-endian u64, then the
+/// value and `\n`.
+#[cfg(all(target_os = "linux", feature = "journald"))]
+fn write_journal_field<T: fmt::Display>(
+    payload: &mut Vec<u8>,
+    name: &str,
+    value: T,
+) -> io::Result<()> {
+    payload.extend_from_slice(name.as_bytes());
+    payload.push(b'\n');
+    let length_start = payload.len();
+    payload.extend_from_slice(&[0; 8]);
+    let value_start = payload.len();
+
+    // Formatting straight into the payload avoids an allocation per field.
+    write!(payload, "{}", value)?;
+
+    if payload[value_start..].contains(&b'\n') {
+        let length = (payload.len() - value_start) as u64;
+        payload[length_start..value_start].copy_from_slice(&length.to_le_bytes());
+    } else {
+        payload.drain(length_start..value_start);
+        payload[length_start - 1] = b'=';
+    }
+    payload.push(b'\n');
+
+    Ok(())
+}
+
+/// Fields set by `Journald` itself, which key-values may not overwrite.
+#[cfg(all(target_os = "linux", feature = "journald"))]
+const JOURNAL_FIELDS: [&str; 8] = [
+    "MESSAGE",
+    "PRIORITY",
+    "TARGET",
+    "CODE_FILE",
+    "CODE_LINE",
+    "CODE_MODULE",
+    "THREAD",
+    "SYSLOG_IDENTIFIER",
+];
+
+/// Prefix for key-values which would otherwise overwrite `JOURNAL_FIELDS`.
+#[cfg(all(target_os = "linux", feature = "journald"))]
+const JOURNAL_KV_PREFIX: &str = "KV_";
+
+/// Converts a key into a valid journal field name, storing it in `name`, or
+/// leaves `name` empty if the key shouldn't be sent.
+///
+/// Field names may only contain uppercase ASCII letters, digits and
+/// underscores, may not start with a digit or underscore, and are at most 64
+/// characters. Keys starting with an underscore are skipped, as journald
+/// reserves those names for fields it adds itself.
+#[cfg(all(target_os = "linux", feature = "journald"))]
+fn journal_field_name(name: &mut String, key: &str) {
+    name.clear();
+    if key.starts_with('_') {
+        return;
+    }
+    for ch in key
+        .trim_start_matches(|c: char| c == '_' || c.is_ascii_digit())
+        .chars()
+    {
+        if name.len() == 64 {
+            break;
+        }
+        if ch.is_ascii_alphanumeric() {
+            name.push(ch.to_ascii_uppercase());
+        } else {
+            name.push('_');
+        }
+    }
+    if JOURNAL_FIELDS.contains(&name.as_str()) {
+        name.insert_str(0, JOURNAL_KV_PREFIX);
+    }
+}
+
 #[inline(always)]
 fn fallback_on_error<F>(record: &log::Record, log_func: F)
 where
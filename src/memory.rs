@@ -0,0 +1,221 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+    time::SystemTime,
+};
+
+use log::Log;
+
+use crate::time::Clock;
+
+/// In-memory output holding the last few formatted records.
+///
+/// A `MemoryBuffer` is a cloneable handle: chain one clone into a
+/// [`Dispatch`], and keep another around to read the stored records back,
+/// for example from an admin endpoint or a test.
+///
+/// Once `capacity` records are stored, each new record replaces the oldest
+/// one.
+///
+/// Example usage:
+///
+/// ```
+/// let buffer = fern::MemoryBuffer::new(1000);
+///
+/// fern::Dispatch::new()
+///     .chain(buffer.clone())
+///     # /*
+///     .apply()?;
+///     # */
+///     # .into_log();
+///
+/// // later
+/// for record in buffer.snapshot_level(log::LevelFilter::Warn) {
+///     println!("{} [{}] {}", record.level(), record.target(), record.message());
+/// }
+/// # Ok::<(), log::SetLoggerError>(())
+/// ```
+///
+/// [`Dispatch`]: struct.Dispatch.html
+#[derive(Clone)]
+pub struct MemoryBuffer {
+    inner: Arc<Mutex<VecDeque<BufferedRecord>>>,
+    capacity: usize,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+/// A record stored in a [`MemoryBuffer`].
+///
+/// [`MemoryBuffer`]: struct.MemoryBuffer.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BufferedRecord {
+    level: log::Level,
+    target: String,
+    timestamp: SystemTime,
+    message: String,
+}
+
+impl MemoryBuffer {
+    /// Creates an empty buffer holding at most `capacity` records.
+    ///
+    /// Space for records is allocated as they're logged, so a large capacity
+    /// costs nothing until it's used.
+    pub fn new(capacity: usize) -> Self {
+        MemoryBuffer {
+            inner: Arc::new(Mutex::new(VecDeque::new())),
+            capacity,
+            clock: None,
+        }
+    }
+
+    /// Creates an empty buffer holding at most `capacity` records, which
+    /// timestamps records with the given clock rather than the
+    /// [`SystemClock`].
+    ///
+    /// All clones of the buffer share the clock.
+    ///
+    /// [`SystemClock`]: time/struct.SystemClock.html
+    pub fn with_clock<C: Clock + 'static>(capacity: usize, clock: C) -> Self {
+        MemoryBuffer {
+            clock: Some(Arc::new(clock)),
+            ..MemoryBuffer::new(capacity)
+        }
+    }
+
+    /// Returns the maximum number of records this buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of records currently stored.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no records are stored.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns a copy of all stored records, oldest first.
+    pub fn snapshot(&self) -> Vec<BufferedRecord> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Returns a copy of all stored records passing the given level filter,
+    /// oldest first.
+    pub fn snapshot_level(&self, level: log::LevelFilter) -> Vec<BufferedRecord> {
+        self.snapshot_matching(|record| record.level <= level)
+    }
+
+    /// Returns a copy of all stored records with the given target, or a
+    /// target in a submodule of it, oldest first.
+    ///
+    /// For example, `"hyper"` matches records with the targets `"hyper"` and
+    /// `"hyper::client"`, but not `"hyperlocal"`.
+    pub fn snapshot_target(&self, target: &str) -> Vec<BufferedRecord> {
+        self.snapshot_matching(|record| target_matches(&record.target, target))
+    }
+
+    /// Returns a copy of all stored records for which `predicate` returns
+    /// `true`, oldest first.
+    pub fn snapshot_matching<F>(&self, mut predicate: F) -> Vec<BufferedRecord>
+    where
+        F: FnMut(&BufferedRecord) -> bool,
+    {
+        self.lock()
+            .iter()
+            .filter(|record| predicate(record))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes and returns all stored records, oldest first.
+    pub fn drain(&self) -> Vec<BufferedRecord> {
+        self.lock().drain(..).collect()
+    }
+
+    /// Removes all stored records.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<BufferedRecord>> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for MemoryBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoryBuffer")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl Log for MemoryBuffer {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        self.capacity > 0
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.capacity == 0 {
+            return;
+        }
+        // format before locking, in case formatting itself logs.
+        let buffered = BufferedRecord {
+            level: record.level(),
+            target: record.target().to_owned(),
+            timestamp: match self.clock {
+                Some(ref clock) => clock.now(),
+                None => SystemTime::now(),
+            },
+            message: record.args().to_string(),
+        };
+
+        let mut records = self.lock();
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(buffered);
+    }
+
+    fn flush(&self) {}
+}
+
+impl BufferedRecord {
+    /// The record's level.
+    pub fn level(&self) -> log::Level {
+        self.level
+    }
+
+    /// The record's target.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The time at which the record was stored.
+    pub fn timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    /// The formatted message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Consumes the record, returning the formatted message.
+    pub fn into_message(self) -> String {
+        self.message
+    }
+}
+
+/// Returns `true` if `target` is `filter`, or a submodule of it.
+pub fn target_matches(target: &str, filter: &str) -> bool {
+    match target.strip_prefix(filter) {
+        Some(rest) => rest.is_empty() || rest.starts_with("::"),
+        None => false,
+    }
+}
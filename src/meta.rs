@@ -72,6 +72,20 @@ This mitigation also has the advantage of ensuring there's only one call to `Dis
 use `meta-logging-in-format` and have multiple backends, `Display::fmt` will still be called once
 per backend. With this, it will only be called once.
 
+[`Dispatch::render_once`] applies this mitigation to all messages passing through a dispatch,
+rendering them into a reused thread-local buffer rather than allocating for each log:
+
+```
+fern::Dispatch::new()
+    .render_once(true)
+    .format(move |out, message, record| {
+        out.finish(format_args!("[{}] {}", record.level(), message))
+    })
+    # ;
+```
+
+[`Dispatch::render_once`]: ../struct.Dispatch.html#method.render_once
+
 ------
 
 If you've never experienced this problem, there's no need to fix it - `Display::fmt` and
@@ -0,0 +1,90 @@
+use std::{any::Any, fmt, panic, thread};
+
+/// Installs a panic hook logging each panic at `Error` level through the
+/// global logger, before calling the previously installed hook.
+///
+/// The logged record's target is `panic`, and its file and line are the
+/// panic's location. The message includes the panicking thread's name, the
+/// location and the panic message, in the same form as Rust's default hook
+/// prints them. All outputs are flushed afterwards, so the message reaches
+/// files and syslog even if the process aborts right after.
+///
+/// With the `backtrace` feature enabled, a backtrace is included if enabled
+/// through the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment
+/// variables. This feature requires Rust 1.65.
+///
+/// Example usage:
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// fern::Dispatch::new()
+///     .chain(fern::log_file("program.log")?)
+///     .apply()?;
+///
+/// fern::install_panic_hook();
+///
+/// panic!("this ends up in program.log");
+/// # }
+/// ```
+///
+/// Since the message is logged while panicking, outputs panicking themselves
+/// (such as [`Panic`]) will abort the process.
+///
+/// [`Panic`]: struct.Panic.html
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let thread = thread::current();
+        let location = info.location();
+        let message = PanicMessage {
+            thread: thread.name().unwrap_or("<unnamed>"),
+            location: location.map(|l| (l.file(), l.line(), l.column())),
+            payload: info.payload(),
+            #[cfg(feature = "backtrace")]
+            backtrace: std::backtrace::Backtrace::capture(),
+        };
+
+        let logger = log::logger();
+        logger.log(
+            &log::RecordBuilder::new()
+                .args(format_args!("{}", message))
+                .level(log::Level::Error)
+                .target("panic")
+                .file(location.map(|l| l.file()))
+                .line(location.map(|l| l.line()))
+                .build(),
+        );
+        logger.flush();
+
+        previous(info);
+    }));
+}
+
+struct PanicMessage<'a> {
+    thread: &'a str,
+    location: Option<(&'a str, u32, u32)>,
+    payload: &'a (dyn Any + Send),
+    #[cfg(feature = "backtrace")]
+    backtrace: std::backtrace::Backtrace,
+}
+
+impl fmt::Display for PanicMessage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "thread '{}' panicked", self.thread)?;
+        if let Some((file, line, column)) = self.location {
+            write!(f, " at {}:{}:{}", file, line, column)?;
+        }
+        if let Some(message) = self.payload.downcast_ref::<&str>() {
+            write!(f, ":\n{}", message)?;
+        } else if let Some(message) = self.payload.downcast_ref::<String>() {
+            write!(f, ":\n{}", message)?;
+        }
+        #[cfg(feature = "backtrace")]
+        {
+            if self.backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+                write!(f, "\nstack backtrace:\n{}", self.backtrace)?;
+            }
+        }
+        Ok(())
+    }
+}
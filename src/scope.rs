@@ -0,0 +1,250 @@
+//! Temporarily overriding the global logger for the current thread.
+//!
+//! [`Dispatch::apply`] can only be called once per process, which makes it
+//! hard to test code logging through the global logger. Instead, fern can
+//! install a proxy as the global logger, sending records either to a
+//! [`Dispatch`] overriding it on the current thread, or to a global fallback.
+//!
+//! [`scoped`] sends all records logged on the current thread within a closure
+//! to a temporary dispatch:
+//!
+//! ```
+//! let buffer = fern::MemoryBuffer::new(10);
+//!
+//! fern::scoped(fern::Dispatch::new().chain(buffer.clone()), || {
+//!     log::info!("hello from the scope");
+//! });
+//!
+//! assert_eq!(buffer.snapshot()[0].message(), "hello from the scope");
+//! ```
+//!
+//! Records logged outside of any scope go to the dispatch given to
+//! [`set_global`], or are discarded if there is none:
+//!
+//! ```
+//! fern::scope::set_global(
+//!     fern::Dispatch::new()
+//!         .level(log::LevelFilter::Info)
+//!         .chain(std::io::stdout()),
+//! )?;
+//! # Ok::<(), log::SetLoggerError>(())
+//! ```
+//!
+//! Scopes don't automatically extend to spawned threads. Use [`spawn`] to
+//! spawn a thread within the current scope, or [`current`] and
+//! [`Handle::enter`] to enter it on an existing thread.
+//!
+//! The proxy never lowers the global maximum log level set through
+//! [`log::set_max_level`], so records filtered out by every dispatch will
+//! still be sent to it once a more verbose dispatch has been used.
+//!
+//! [`Dispatch`]: ../struct.Dispatch.html
+//! [`Dispatch::apply`]: ../struct.Dispatch.html#method.apply
+//! [`log::set_max_level`]: https://docs.rs/log/0.4/log/fn.set_max_level.html
+use std::{
+    cell::RefCell,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Once, RwLock,
+    },
+    thread,
+};
+
+use log::Log;
+
+use crate::Dispatch;
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<dyn Log>>> = const { RefCell::new(None) };
+}
+
+/// The global logger, once installed.
+struct Proxy {
+    fallback: RwLock<Option<Box<dyn Log>>>,
+}
+
+static PROXY: Proxy = Proxy {
+    fallback: RwLock::new(None),
+};
+
+/// A handle to the scope active on some thread, which can be entered on
+/// other threads.
+///
+/// Created with [`current`].
+#[derive(Clone, Default)]
+pub struct Handle {
+    logger: Option<Arc<dyn Log>>,
+}
+
+/// Restores the previous scope when dropped, even when panicking.
+struct Reset {
+    previous: Option<Arc<dyn Log>>,
+}
+
+/// Runs `f`, sending all records logged on the current thread to `dispatch`
+/// instead of the global logger.
+///
+/// Scopes can be nested; the innermost one receives records.
+///
+/// This installs fern's proxy as the global logger if it hasn't been yet.
+///
+/// # Panics
+///
+/// This panics if a global logger other than the proxy has already been set.
+pub fn scoped<F, R>(dispatch: Dispatch, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    if install().is_err() {
+        panic!("fern::scoped: another global logger has already been set");
+    }
+
+    let (max_level, logger) = dispatch.into_log();
+    raise_max_level(max_level);
+
+    Handle {
+        logger: Some(Arc::from(logger)),
+    }
+    .enter(f)
+}
+
+/// Installs fern's proxy as the global logger, sending records logged
+/// outside of any scope to `dispatch`.
+///
+/// This can be called again to replace the fallback dispatch.
+///
+/// # Errors
+///
+/// This returns an error if a global logger other than the proxy has
+/// already been set.
+pub fn set_global(dispatch: Dispatch) -> Result<(), log::SetLoggerError> {
+    install()?;
+
+    let (max_level, logger) = dispatch.into_log();
+    *PROXY.fallback.write().unwrap_or_else(|e| e.into_inner()) = Some(logger);
+    raise_max_level(max_level);
+
+    Ok(())
+}
+
+/// Returns a handle to the scope active on the current thread.
+///
+/// If no scope is active, entering the handle clears any scope instead.
+pub fn current() -> Handle {
+    Handle {
+        logger: CURRENT.with(|current| current.borrow().clone()),
+    }
+}
+
+/// Spawns a new thread which sends records to the scope active on the
+/// current thread, if any.
+///
+/// See [`std::thread::spawn`].
+///
+/// [`std::thread::spawn`]: https://doc.rust-lang.org/std/thread/fn.spawn.html
+pub fn spawn<F, T>(f: F) -> thread::JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let handle = current();
+    thread::spawn(move || handle.enter(f))
+}
+
+impl Handle {
+    /// Runs `f` with this scope active on the current thread.
+    pub fn enter<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let _reset = Reset {
+            previous: CURRENT.with(|current| current.replace(self.logger.clone())),
+        };
+        f()
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("active", &self.logger.is_some())
+            .finish()
+    }
+}
+
+impl Drop for Reset {
+    fn drop(&mut self) {
+        // ignore errors - the thread is shutting down.
+        let _ = CURRENT.try_with(|current| *current.borrow_mut() = self.previous.take());
+    }
+}
+
+impl Log for Proxy {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        match current_logger() {
+            Some(logger) => logger.enabled(metadata),
+            None => self
+                .fallback
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_ref()
+                .map_or(false, |fallback| fallback.enabled(metadata)),
+        }
+    }
+
+    fn log(&self, record: &log::Record) {
+        match current_logger() {
+            Some(logger) => logger.log(record),
+            None => {
+                if let Some(fallback) = &*self.fallback.read().unwrap_or_else(|e| e.into_inner()) {
+                    fallback.log(record)
+                }
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = current_logger() {
+            logger.flush();
+        }
+        if let Some(fallback) = &*self.fallback.read().unwrap_or_else(|e| e.into_inner()) {
+            fallback.flush();
+        }
+    }
+}
+
+/// Clones the current scope's logger, so that it isn't borrowed while
+/// logging.
+fn current_logger() -> Option<Arc<dyn Log>> {
+    CURRENT
+        .try_with(|current| current.borrow().clone())
+        .ok()
+        .flatten()
+}
+
+fn raise_max_level(level: log::LevelFilter) {
+    if level > log::max_level() {
+        log::set_max_level(level);
+    }
+}
+
+/// Installs the proxy as the global logger, if nothing else was set first.
+fn install() -> Result<(), log::SetLoggerError> {
+    static INSTALL: Once = Once::new();
+    static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+    let mut result = Ok(());
+    INSTALL.call_once(|| {
+        result = log::set_logger(&PROXY);
+        INSTALLED.store(result.is_ok(), Ordering::SeqCst);
+    });
+    match result {
+        Ok(()) if !INSTALLED.load(Ordering::SeqCst) => {
+            // another logger was set before the first call - this fails in
+            // the same way that call did.
+            log::set_logger(&PROXY)
+        }
+        result => result,
+    }
+}
@@ -0,0 +1,177 @@
+//! Support for the `<N>` priority prefixes understood by systemd.
+//!
+//! When a service's stdout or stderr is connected to the journal, journald
+//! reads a `<N>` prefix at the start of each line as that line's syslog
+//! priority (see [`sd-daemon(3)`][sd-daemon]). This module provides a
+//! formatter adding these prefixes.
+//!
+//! The simplest setup uses [`format()`] as the dispatch formatter:
+//!
+//! ```
+//! fern::Dispatch::new()
+//!     .format(fern::sd_daemon::format)
+//!     .chain(std::io::stderr())
+//!     # .into_log();
+//! ```
+//!
+//! To include more in each message, wrap it with [`prefixed`] instead:
+//!
+//! ```
+//! fern::Dispatch::new()
+//!     .format(|out, message, record| {
+//!         out.finish(format_args!(
+//!             "{}",
+//!             fern::sd_daemon::prefixed(
+//!                 record.level(),
+//!                 format_args!("[{}] {}", record.target(), message)
+//!             )
+//!         ))
+//!     })
+//!     .chain(std::io::stderr())
+//!     # .into_log();
+//! ```
+//!
+//! journald treats each line separately, so the prefix is repeated at the
+//! start of every line in multiline messages. Otherwise, continuation lines
+//! would be logged with the default priority.
+//!
+//! Levels are translated into priorities in the same way as for syslog; see
+//! [`SyslogSeverity`].
+//!
+//! [sd-daemon]: https://www.freedesktop.org/software/systemd/man/sd-daemon.html
+//! [`SyslogSeverity`]: ../enum.SyslogSeverity.html
+use std::fmt::{self, Write};
+
+use crate::{FormatCallback, SyslogSeverity};
+
+/// Opaque structure which represents some text and the priority to prefix
+/// each of its lines with.
+///
+/// This implements [`fmt::Display`], and can be created and displayed
+/// without any allocation.
+///
+/// See [`prefixed`] and [`Prefixed::with_severity`].
+pub struct Prefixed<T>
+where
+    T: fmt::Display,
+{
+    text: T,
+    priority: u8,
+}
+
+impl<T> Prefixed<T>
+where
+    T: fmt::Display,
+{
+    /// Prefixes the lines of `text` with the given severity, rather than one
+    /// translated from a log level.
+    pub fn with_severity(severity: SyslogSeverity, text: T) -> Self {
+        Prefixed {
+            text,
+            priority: severity.code(),
+        }
+    }
+}
+
+/// Prefixes each line of `text` with the priority corresponding to `level`.
+pub fn prefixed<T>(level: log::Level, text: T) -> Prefixed<T>
+where
+    T: fmt::Display,
+{
+    Prefixed::with_severity(level.into(), text)
+}
+
+/// A formatter which prefixes each line of each message with its priority.
+///
+/// This can be passed directly to [`Dispatch::format`].
+///
+/// [`Dispatch::format`]: ../struct.Dispatch.html#method.format
+pub fn format(out: FormatCallback, message: &fmt::Arguments, record: &log::Record) {
+    out.finish(format_args!("{}", prefixed(record.level(), message)))
+}
+
+impl<T> fmt::Display for Prefixed<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut writer = PrefixWriter {
+            inner: f,
+            priority: self.priority,
+            at_line_start: true,
+        };
+        write!(writer, "{}", self.text)
+    }
+}
+
+/// Writer inserting the prefix before the first text of each line.
+///
+/// The prefix is only written once something follows a newline, so messages
+/// ending in a newline don't end with a dangling prefix.
+struct PrefixWriter<'a, 'b> {
+    inner: &'a mut fmt::Formatter<'b>,
+    priority: u8,
+    at_line_start: bool,
+}
+
+impl fmt::Write for PrefixWriter<'_, '_> {
+    fn write_str(&mut self, mut s: &str) -> fmt::Result {
+        while !s.is_empty() {
+            if self.at_line_start {
+                write!(self.inner, "<{}>", self.priority)?;
+                self.at_line_start = false;
+            }
+            match s.find('\n') {
+                Some(index) => {
+                    self.inner.write_str(&s[..=index])?;
+                    self.at_line_start = true;
+                    s = &s[index + 1..];
+                }
+                None => {
+                    self.inner.write_str(s)?;
+                    s = "";
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use log::Level::*;
+
+    use super::{prefixed, Prefixed};
+    use crate::SyslogSeverity;
+
+    #[test]
+    fn prefixes_single_line() {
+        assert_eq!(prefixed(Error, "failed").to_string(), "<3>failed");
+        assert_eq!(prefixed(Warn, "careful").to_string(), "<4>careful");
+        assert_eq!(prefixed(Info, "hello").to_string(), "<6>hello");
+        assert_eq!(prefixed(Debug, "details").to_string(), "<7>details");
+        assert_eq!(prefixed(Trace, "more").to_string(), "<7>more");
+    }
+
+    #[test]
+    fn prefixes_every_line() {
+        assert_eq!(
+            prefixed(Error, format_args!("{}\n{}\n\n{}", "one", "two", "four")).to_string(),
+            "<3>one\n<3>two\n<3>\n<3>four"
+        );
+    }
+
+    #[test]
+    fn no_dangling_prefix() {
+        assert_eq!(prefixed(Info, "line\n").to_string(), "<6>line\n");
+        assert_eq!(prefixed(Info, "").to_string(), "");
+    }
+
+    #[test]
+    fn custom_severity() {
+        assert_eq!(
+            Prefixed::with_severity(SyslogSeverity::Notice, "started").to_string(),
+            "<5>started"
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Utilities for asserting on log output in tests.
+//!
+//! [`capture()`] returns a [`Capture`] which stores each record logged on the
+//! current thread while it's alive. Since the test harness runs each test on
+//! its own thread, tests running in parallel don't see each other's logs.
+//!
+//! ```
+//! use fern::assert_logged;
+//! use log::Level;
+//!
+//! # fn do_query() { log::warn!(target: "db", "query timeout after 30s"); }
+//! # fn main() {
+//! let capture = fern::testing::capture();
+//!
+//! do_query();
+//!
+//! assert_logged!(capture, Level::Warn, target = "db", contains "timeout");
+//! # }
+//! ```
+//!
+//! [`capture()`] installs fern's scope proxy as the global logger the first
+//! time it's called, unless another logger has been set already, so it can
+//! be used alongside [`scoped`](../scope/fn.scoped.html) in the same test
+//! binary. To capture records while using another global logger, chain
+//! [`output()`] into it instead.
+//!
+//! Records logged on threads spawned by a test are not captured by that
+//! test. To capture everything sent to a specific dispatch instead, chain a
+//! [`Capture`] into it directly:
+//!
+//! ```
+//! let capture = fern::testing::Capture::new();
+//!
+//! let (_max_level, logger) = fern::Dispatch::new()
+//!     .chain(capture.clone())
+//!     .into_log();
+//! ```
+use std::{
+    cell::RefCell,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard, Once, Weak},
+};
+
+use log::Log;
+
+use crate::{memory::target_matches, scope, Dispatch, Output};
+
+type Records = Mutex<Vec<CapturedRecord>>;
+
+thread_local! {
+    static THREAD_CAPTURES: RefCell<Vec<Weak<Records>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Handle to a list of captured records.
+///
+/// Clones refer to the same list.
+///
+/// See the [module documentation] for more details.
+///
+/// [module documentation]: index.html
+#[derive(Clone, Default)]
+pub struct Capture {
+    records: Arc<Records>,
+}
+
+/// A record stored by a [`Capture`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapturedRecord {
+    level: log::Level,
+    target: String,
+    message: String,
+    file: Option<String>,
+    line: Option<u32>,
+    key_values: Vec<(String, String)>,
+}
+
+/// A set of conditions a [`CapturedRecord`] can match.
+///
+/// This is usually built by [`assert_logged!`](../macro.assert_logged.html).
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    level: Option<log::Level>,
+    target: Option<String>,
+    contains: Option<String>,
+}
+
+/// Returns a new [`Capture`] storing records logged on the current thread
+/// until it and all its clones are dropped.
+///
+/// The first time it's called, this installs fern's [scope proxy] as the
+/// global logger, sending records logged outside of any scope to
+/// [`output()`]. This replaces any fallback set earlier with
+/// [`scope::set_global`]. If another global logger has already been set,
+/// records are only captured if [`output()`] is chained into it.
+///
+/// [scope proxy]: ../scope/index.html
+/// [`scope::set_global`]: ../scope/fn.set_global.html
+pub fn capture() -> Capture {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        // ignore errors - another logger was already installed.
+        let _ = scope::set_global(
+            Dispatch::new()
+                .level(log::LevelFilter::Trace)
+                .chain(output()),
+        );
+    });
+
+    let capture = Capture::new();
+    THREAD_CAPTURES.with(|captures| captures.borrow_mut().push(Arc::downgrade(&capture.records)));
+    capture
+}
+
+/// Returns an output which stores each record in the captures created by
+/// [`capture()`] on the thread it's logged on.
+///
+/// This is only needed when setting up the global logger manually.
+pub fn output() -> Output {
+    Output::from(Box::new(ThreadCaptures) as Box<dyn Log>)
+}
+
+impl Capture {
+    /// Returns a new, empty capture.
+    ///
+    /// This isn't registered to the current thread; chain it into a
+    /// [`Dispatch`] to store records.
+    ///
+    /// [`Dispatch`]: ../struct.Dispatch.html
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of all records captured so far.
+    pub fn records(&self) -> Vec<CapturedRecord> {
+        self.lock().clone()
+    }
+
+    /// Returns the messages of all records captured so far.
+    pub fn messages(&self) -> Vec<String> {
+        self.lock()
+            .iter()
+            .map(|record| record.message().to_owned())
+            .collect()
+    }
+
+    /// Returns all captured records matching the query.
+    pub fn find(&self, query: &Query) -> Vec<CapturedRecord> {
+        self.lock()
+            .iter()
+            .filter(|record| query.matches(record))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns `true` if any captured record matches the query.
+    pub fn contains(&self, query: &Query) -> bool {
+        self.lock().iter().any(|record| query.matches(record))
+    }
+
+    /// Removes all captured records.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Panics unless a captured record matches the query.
+    ///
+    /// The panic message lists all captured records.
+    #[track_caller]
+    pub fn assert_logged(&self, query: &Query) {
+        let records = self.lock();
+        if records.iter().any(|record| query.matches(record)) {
+            return;
+        }
+        let mut message = format!("no record matching {} was logged. captured:", query);
+        if records.is_empty() {
+            message.push_str(" nothing");
+        }
+        for record in records.iter() {
+            message.push_str(&format!("\n    {}", record));
+        }
+        drop(records);
+        panic!("{}", message);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<CapturedRecord>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl fmt::Debug for Capture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Capture")
+            .field("records", &*self.lock())
+            .finish()
+    }
+}
+
+impl Log for Capture {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let captured = CapturedRecord::new(record);
+        self.lock().push(captured);
+    }
+
+    fn flush(&self) {}
+}
+
+impl From<Capture> for Output {
+    /// Creates an output logger storing all records in the capture.
+    fn from(capture: Capture) -> Self {
+        Output::from(Box::new(capture) as Box<dyn Log>)
+    }
+}
+
+struct ThreadCaptures;
+
+impl Log for ThreadCaptures {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        // build the record before borrowing, in case formatting itself logs.
+        let captured = CapturedRecord::new(record);
+        // ignore errors - the thread is shutting down.
+        let _ = THREAD_CAPTURES.try_with(|captures| {
+            let mut captures = match captures.try_borrow_mut() {
+                Ok(captures) => captures,
+                Err(_) => return,
+            };
+            captures.retain(|records| match records.upgrade() {
+                Some(records) => {
+                    records
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(captured.clone());
+                    true
+                }
+                None => false,
+            });
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+impl CapturedRecord {
+    fn new(record: &log::Record) -> Self {
+        #[cfg_attr(not(feature = "kv"), allow(unused_mut))]
+        let mut key_values = Vec::new();
+        #[cfg(feature = "kv")]
+        {
+            struct Collect<'a>(&'a mut Vec<(String, String)>);
+
+            impl<'kvs> log::kv::VisitSource<'kvs> for Collect<'_> {
+                fn visit_pair(
+                    &mut self,
+                    key: log::kv::Key<'kvs>,
+                    value: log::kv::Value<'kvs>,
+                ) -> Result<(), log::kv::Error> {
+                    self.0.push((key.to_string(), value.to_string()));
+                    Ok(())
+                }
+            }
+
+            let _ = record.key_values().visit(&mut Collect(&mut key_values));
+        }
+
+        CapturedRecord {
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+            file: record.file().map(ToOwned::to_owned),
+            line: record.line(),
+            key_values,
+        }
+    }
+
+    /// The record's level.
+    pub fn level(&self) -> log::Level {
+        self.level
+    }
+
+    /// The record's target.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// The message, as formatted by any dispatches it passed through.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The source file the record was logged from, if known.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// The line the record was logged from, if known.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The record's key-values, formatted with `Display`.
+    ///
+    /// This is always empty unless the `kv` feature is enabled.
+    pub fn key_values(&self) -> &[(String, String)] {
+        &self.key_values
+    }
+
+    /// Returns the value of the given key, if present.
+    pub fn key_value(&self, key: &str) -> Option<&str> {
+        self.key_values
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl fmt::Display for CapturedRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{} {}] {}", self.level, self.target, self.message)
+    }
+}
+
+impl Query {
+    /// Returns a query matching all records.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match records with exactly this level.
+    #[must_use]
+    pub fn level(mut self, level: log::Level) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Only match records with this target, or a target in a submodule of
+    /// it.
+    #[must_use]
+    pub fn target<T: Into<String>>(mut self, target: T) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Only match records whose message contains this text.
+    #[must_use]
+    pub fn contains<T: Into<String>>(mut self, text: T) -> Self {
+        self.contains = Some(text.into());
+        self
+    }
+
+    /// Returns `true` if the record matches all of this query's conditions.
+    pub fn matches(&self, record: &CapturedRecord) -> bool {
+        self.level.map_or(true, |level| record.level == level)
+            && self
+                .target
+                .as_ref()
+                .map_or(true, |target| target_matches(&record.target, target))
+            && self
+                .contains
+                .as_ref()
+                .map_or(true, |text| record.message.contains(&**text))
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.level {
+            Some(level) => write!(f, "level = {}", level)?,
+            None => write!(f, "any level")?,
+        }
+        if let Some(target) = &self.target {
+            write!(f, ", target = {:?}", target)?;
+        }
+        if let Some(text) = &self.contains {
+            write!(f, ", contains {:?}", text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Asserts that a [`Capture`] holds a record with the given level, and
+/// optionally target and message text.
+///
+/// ```
+/// use fern::assert_logged;
+/// use log::Level;
+///
+/// let capture = fern::testing::capture();
+/// log::warn!(target: "db::pool", "connection timeout");
+///
+/// assert_logged!(capture, Level::Warn);
+/// assert_logged!(capture, Level::Warn, target = "db");
+/// assert_logged!(capture, Level::Warn, contains "timeout");
+/// assert_logged!(capture, Level::Warn, target = "db", contains "timeout");
+/// ```
+///
+/// On failure, the panic message lists all captured records.
+///
+/// [`Capture`]: testing/struct.Capture.html
+#[macro_export]
+macro_rules! assert_logged {
+    ($capture:expr, $level:expr $(, target = $target:expr)? $(, contains $text:expr)? $(,)?) => {
+        $capture.assert_logged(
+            &$crate::testing::Query::new()
+                .level($level)
+                $(.target($target))?
+                $(.contains($text))?
+        )
+    };
+}
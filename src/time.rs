@@ -0,0 +1,605 @@
+//! Formatting timestamps without any time library.
+//!
+//! [`Rfc3339`] formats a [`SystemTime`] as an [RFC 3339] timestamp in UTC,
+//! such as `2018-02-14T00:28:07Z`, with a configurable precision:
+//!
+//! ```
+//! use fern::time::{Precision, Rfc3339};
+//!
+//! let timestamps = Rfc3339::new().precision(Precision::Millis);
+//!
+//! fern::Dispatch::new()
+//!     .format(move |out, message, record| {
+//!         out.finish(format_args!(
+//!             // "[2018-02-14T00:28:07.123Z INFO] message"
+//!             "[{} {}] {}",
+//!             timestamps.now(),
+//!             record.level(),
+//!             message
+//!         ))
+//!     })
+//!     # .into_log();
+//! ```
+//!
+//! Formatting allocates nothing. The date and time of day are only computed
+//! once per second on each thread, and reused for other timestamps within the
+//! same second.
+//!
+//! # Time zones
+//!
+//! With the `timezone` feature, timestamps can be in local time, read from the
+//! system's zoneinfo database, or at a fixed offset from UTC. This doesn't need
+//! any time library either:
+//!
+//! ```
+//! # #[cfg(feature = "timezone")] {
+//! use fern::time::{Rfc3339, Timezone, UtcOffset};
+//!
+//! // "2018-02-14T01:28:07+01:00" in Berlin
+//! let local = Rfc3339::new().timezone(Timezone::local());
+//!
+//! // "2018-02-14T05:58:07+05:30"
+//! let india = Rfc3339::new().timezone("+05:30".parse::<UtcOffset>().unwrap());
+//! # }
+//! ```
+//!
+//! The same [`Timezone`] can name [`DateBased`] log files.
+//!
+//! [`Rfc3339`]: struct.Rfc3339.html
+//! [`Timezone`]: struct.Timezone.html
+//! [`DateBased`]: ../struct.DateBased.html#method.timezone
+//! [`SystemTime`]: https://doc.rust-lang.org/std/time/struct.SystemTime.html
+//! [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+use std::{
+    cell::Cell,
+    fmt,
+    panic::RefUnwindSafe,
+    str,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "timezone")]
+use std::{error, str::FromStr};
+
+#[cfg(feature = "timezone")]
+mod zoneinfo;
+
+/// Length of `YYYY-MM-DDTHH:MM:SS`.
+const DATE_TIME_LEN: usize = 19;
+
+thread_local! {
+    /// The last second formatted on this thread, and its date and time.
+    static LAST_SECOND: Cell<(i64, [u8; DATE_TIME_LEN])> =
+        const { Cell::new((i64::MIN, [0; DATE_TIME_LEN])) };
+}
+
+/// Precision of the fractional seconds in a timestamp.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Precision {
+    /// No fractional seconds: `2018-02-14T00:28:07Z`.
+    Seconds,
+    /// Milliseconds: `2018-02-14T00:28:07.123Z`.
+    Millis,
+    /// Microseconds: `2018-02-14T00:28:07.123456Z`.
+    Micros,
+    /// Nanoseconds: `2018-02-14T00:28:07.123456789Z`.
+    Nanos,
+}
+
+/// Configuration for formatting [RFC 3339] timestamps.
+///
+/// See the [module documentation] for more details.
+///
+/// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+/// [module documentation]: index.html
+#[derive(Clone, Debug)]
+#[must_use = "builder methods take config by value and thus must be reassigned to variable"]
+pub struct Rfc3339 {
+    precision: Precision,
+    #[cfg(feature = "timezone")]
+    timezone: Timezone,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+/// A timestamp formatted when displayed.
+///
+/// Created with [`Rfc3339::format`] or [`Rfc3339::now`].
+///
+/// [`Rfc3339::format`]: struct.Rfc3339.html#method.format
+/// [`Rfc3339::now`]: struct.Rfc3339.html#method.now
+#[derive(Copy, Clone, Debug)]
+pub struct Timestamp<'a> {
+    time: SystemTime,
+    config: &'a Rfc3339,
+}
+
+/// A source of the current time.
+///
+/// Components which depend on the time, such as [`DateBased`] file names,
+/// [`MemoryBuffer`] timestamps and [`Rfc3339::now`], ask a clock for it. They
+/// use the [`SystemClock`] unless given another, such as a [`ManualClock`] in
+/// tests.
+///
+/// [`DateBased`]: ../struct.DateBased.html#method.clock
+/// [`MemoryBuffer`]: ../struct.MemoryBuffer.html#method.with_clock
+/// [`Rfc3339::now`]: struct.Rfc3339.html#method.now
+/// [`SystemClock`]: struct.SystemClock.html
+/// [`ManualClock`]: struct.ManualClock.html
+pub trait Clock: fmt::Debug + Send + Sync + RefUnwindSafe {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// The system's clock, as read by [`SystemTime::now`].
+///
+/// [`SystemTime::now`]: https://doc.rust-lang.org/std/time/struct.SystemTime.html#method.now
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+/// A clock which only moves when told to, for testing.
+///
+/// Clones share the same time, so one clone can be given to fern while
+/// another is used to move it.
+///
+/// Example usage:
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use fern::time::{ManualClock, Rfc3339};
+///
+/// let clock = ManualClock::new(UNIX_EPOCH);
+/// let timestamps = Rfc3339::new().clock(clock.clone());
+///
+/// assert_eq!(timestamps.now().to_string(), "1970-01-01T00:00:00Z");
+/// clock.advance(Duration::from_secs(90));
+/// assert_eq!(timestamps.now().to_string(), "1970-01-01T00:01:30Z");
+/// ```
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    time: Arc<Mutex<SystemTime>>,
+}
+
+/// An offset from UTC, such as `+05:30`.
+///
+/// Parsed from `Z`, or a sign followed by hours and optionally minutes, such
+/// as `-08:00`, `+0530` or `+01`.
+///
+/// Requires the `timezone` feature.
+#[cfg(feature = "timezone")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UtcOffset {
+    seconds: i32,
+}
+
+/// A time zone: UTC, local time, a fixed offset, or a POSIX `TZ` rule.
+///
+/// Requires the `timezone` feature.
+#[cfg(feature = "timezone")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timezone(TimezoneInner);
+
+#[cfg(feature = "timezone")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TimezoneInner {
+    Utc,
+    Local,
+    Fixed(UtcOffset),
+    Rules(Arc<zoneinfo::Zone>),
+}
+
+/// Error parsing a [`UtcOffset`] or [`Timezone`].
+///
+/// [`UtcOffset`]: struct.UtcOffset.html
+/// [`Timezone`]: struct.Timezone.html
+#[cfg(feature = "timezone")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    kind: &'static str,
+}
+
+impl Rfc3339 {
+    /// Creates a configuration formatting timestamps in UTC, with second
+    /// precision.
+    pub fn new() -> Self {
+        Rfc3339 {
+            precision: Precision::Seconds,
+            #[cfg(feature = "timezone")]
+            timezone: Timezone::utc(),
+            clock: None,
+        }
+    }
+
+    /// Sets the precision of fractional seconds.
+    ///
+    /// Default is [`Precision::Seconds`].
+    ///
+    /// [`Precision::Seconds`]: enum.Precision.html#variant.Seconds
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the time zone timestamps are written in.
+    ///
+    /// Default is UTC, written with a `Z` suffix. Other time zones are written
+    /// with their offset at the time, such as `+05:30`. Offsets are truncated
+    /// to whole minutes, as RFC 3339 doesn't allow seconds.
+    ///
+    /// Requires the `timezone` feature.
+    #[cfg(feature = "timezone")]
+    pub fn timezone<T: Into<Timezone>>(mut self, timezone: T) -> Self {
+        self.timezone = timezone.into();
+        self
+    }
+
+    /// Sets the clock [`now`] reads the current time from.
+    ///
+    /// Default is the [`SystemClock`].
+    ///
+    /// [`now`]: #method.now
+    /// [`SystemClock`]: struct.SystemClock.html
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Returns the given time, formatted when displayed.
+    pub fn format(&self, time: SystemTime) -> Timestamp<'_> {
+        Timestamp { time, config: self }
+    }
+
+    /// Returns the current time, formatted when displayed.
+    pub fn now(&self) -> Timestamp<'_> {
+        self.format(match self.clock {
+            Some(ref clock) => clock.now(),
+            None => SystemTime::now(),
+        })
+    }
+
+    /// Returns the offset to write at the given unix time, or `None` for `Z`.
+    #[cfg(feature = "timezone")]
+    fn offset_at(&self, seconds: i64) -> Option<i32> {
+        match self.timezone.0 {
+            TimezoneInner::Utc => None,
+            _ => {
+                let offset = self.timezone.offset_seconds_at(seconds);
+                Some(offset - offset % 60)
+            }
+        }
+    }
+
+    #[cfg(not(feature = "timezone"))]
+    fn offset_at(&self, _seconds: i64) -> Option<i32> {
+        None
+    }
+}
+
+impl Default for Rfc3339 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Timestamp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (seconds, nanos) = unix_time(self.time);
+        let offset = self.config.offset_at(seconds);
+        let local_seconds = seconds + i64::from(offset.unwrap_or(0));
+
+        match cached_date_time(local_seconds) {
+            // only ASCII digits and separators are cached, so this never fails.
+            Some(date_time) => {
+                let date_time = str::from_utf8(&date_time).map_err(|_| fmt::Error)?;
+                f.write_str(date_time)?
+            }
+            None => write_date_time(f, local_seconds)?,
+        }
+        match self.config.precision {
+            Precision::Seconds => {}
+            Precision::Millis => write!(f, ".{:03}", nanos / 1_000_000)?,
+            Precision::Micros => write!(f, ".{:06}", nanos / 1_000)?,
+            Precision::Nanos => write!(f, ".{:09}", nanos)?,
+        }
+        match offset {
+            None => f.write_str("Z"),
+            Some(offset) => write_offset(f, offset, false),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at the given time.
+    pub fn new(time: SystemTime) -> Self {
+        ManualClock {
+            time: Arc::new(Mutex::new(time)),
+        }
+    }
+
+    /// Moves the clock to the given time, which may be in the past.
+    pub fn set(&self, time: SystemTime) {
+        *self.lock() = time;
+    }
+
+    /// Moves the clock forward by the given duration.
+    pub fn advance(&self, duration: Duration) {
+        *self.lock() += duration;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SystemTime> {
+        self.time.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.lock()
+    }
+}
+
+#[cfg(feature = "timezone")]
+impl UtcOffset {
+    /// The offset of UTC itself, `+00:00`.
+    pub const UTC: UtcOffset = UtcOffset { seconds: 0 };
+
+    /// Creates an offset of the given seconds east of UTC, or `None` if it's a
+    /// day or more.
+    pub fn from_seconds(seconds: i32) -> Option<Self> {
+        if seconds.abs() < 86_400 {
+            Some(UtcOffset { seconds })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the seconds east of UTC this offset is.
+    pub fn seconds(self) -> i32 {
+        self.seconds
+    }
+}
+
+#[cfg(feature = "timezone")]
+impl FromStr for UtcOffset {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        let error = ParseError {
+            kind: "invalid UTC offset",
+        };
+        if s == "Z" || s == "z" {
+            return Ok(UtcOffset::UTC);
+        }
+        let (negative, rest) = match s.as_bytes().first() {
+            Some(b'+') => (false, &s[1..]),
+            Some(b'-') => (true, &s[1..]),
+            _ => return Err(error),
+        };
+        let (hours, minutes) = match rest.len() {
+            _ if !rest.is_ascii() => return Err(error),
+            2 => (rest, "00"),
+            4 => rest.split_at(2),
+            5 if rest.as_bytes()[2] == b':' => (&rest[..2], &rest[3..]),
+            _ => return Err(error),
+        };
+        let number = |digits: &str, max: i32| match digits.parse::<i32>() {
+            Ok(n) if n <= max && digits.bytes().all(|b| b.is_ascii_digit()) => Ok(n),
+            _ => Err(error.clone()),
+        };
+        let (hours, minutes) = (number(hours, 23)?, number(minutes, 59)?);
+        let seconds = hours * 3600 + minutes * 60;
+        Ok(UtcOffset {
+            seconds: if negative { -seconds } else { seconds },
+        })
+    }
+}
+
+#[cfg(feature = "timezone")]
+impl fmt::Display for UtcOffset {
+    /// Writes the offset as `+05:30`, followed by seconds if there are any.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_offset(f, self.seconds, true)
+    }
+}
+
+#[cfg(feature = "timezone")]
+impl Timezone {
+    /// UTC.
+    pub fn utc() -> Self {
+        Timezone(TimezoneInner::Utc)
+    }
+
+    /// The system's local time zone.
+    ///
+    /// This is read from the `TZ` environment variable, which may name a
+    /// zone in the zoneinfo database, such as `Europe/Berlin`, give the path
+    /// of a zoneinfo file, or hold a POSIX rule. Without `TZ`,
+    /// `/etc/localtime` is read. If neither works, such as on Windows without
+    /// `TZ` set, local time is UTC.
+    ///
+    /// The rules are loaded once, on first use, and shared by the whole
+    /// process.
+    pub fn local() -> Self {
+        Timezone(TimezoneInner::Local)
+    }
+
+    /// A time zone always at the given offset.
+    pub fn fixed(offset: UtcOffset) -> Self {
+        Timezone(TimezoneInner::Fixed(offset))
+    }
+
+    /// A time zone following a POSIX `TZ` rule, such as
+    /// `CET-1CEST,M3.5.0,M10.5.0/3`.
+    ///
+    /// Note that POSIX offsets are west of UTC, so `CET-1` is one hour ahead
+    /// of UTC.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let new_york = fern::time::Timezone::posix("EST5EDT,M3.2.0,M11.1.0").unwrap();
+    /// ```
+    pub fn posix(rule: &str) -> Result<Self, ParseError> {
+        match zoneinfo::parse_rule(rule) {
+            Some(rule) => Ok(Timezone(TimezoneInner::Rules(Arc::new(rule.into())))),
+            None => Err(ParseError {
+                kind: "invalid POSIX TZ rule",
+            }),
+        }
+    }
+
+    /// Returns the offset from UTC in effect at the given time.
+    pub fn offset_at(&self, time: SystemTime) -> UtcOffset {
+        UtcOffset {
+            seconds: self.offset_seconds_at(unix_time(time).0),
+        }
+    }
+
+    fn offset_seconds_at(&self, seconds: i64) -> i32 {
+        match self.0 {
+            TimezoneInner::Utc => 0,
+            TimezoneInner::Local => zoneinfo::local().offset_at(seconds),
+            TimezoneInner::Fixed(offset) => offset.seconds,
+            TimezoneInner::Rules(ref zone) => zone.offset_at(seconds),
+        }
+        // zoneinfo files could hold any offset.
+        .clamp(-86_399, 86_399)
+    }
+}
+
+#[cfg(feature = "timezone")]
+impl Default for Timezone {
+    fn default() -> Self {
+        Self::utc()
+    }
+}
+
+#[cfg(feature = "timezone")]
+impl From<UtcOffset> for Timezone {
+    fn from(offset: UtcOffset) -> Self {
+        Self::fixed(offset)
+    }
+}
+
+#[cfg(feature = "timezone")]
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.kind)
+    }
+}
+
+#[cfg(feature = "timezone")]
+impl error::Error for ParseError {}
+
+/// Writes an offset as `+05:30`, and `:SS` if `seconds` is set and there are
+/// any.
+fn write_offset(f: &mut fmt::Formatter, offset: i32, seconds: bool) -> fmt::Result {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    write!(f, "{}{:02}:{:02}", sign, offset / 3600, offset / 60 % 60)?;
+    if seconds && offset % 60 != 0 {
+        write!(f, ":{:02}", offset % 60)?;
+    }
+    Ok(())
+}
+
+/// Returns the seconds since the unix epoch, rounded down, and nanoseconds
+/// within that second.
+fn unix_time(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+        Err(before) => {
+            let before = before.duration();
+            match before.subsec_nanos() {
+                0 => (-(before.as_secs() as i64), 0),
+                nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+            }
+        }
+    }
+}
+
+/// Returns `YYYY-MM-DDTHH:MM:SS` for the given second, if the year has four
+/// digits.
+fn cached_date_time(seconds: i64) -> Option<[u8; DATE_TIME_LEN]> {
+    let compute = || {
+        let mut buffer = Buffer([0; DATE_TIME_LEN], 0);
+        match write_date_time(&mut buffer, seconds) {
+            Ok(()) if buffer.1 == DATE_TIME_LEN => Some(buffer.0),
+            _ => None,
+        }
+    };
+    LAST_SECOND
+        .try_with(|last| match last.get() {
+            (cached, date_time) if cached == seconds => Some(date_time),
+            _ => {
+                let date_time = compute()?;
+                last.set((seconds, date_time));
+                Some(date_time)
+            }
+        })
+        .unwrap_or_else(|_| compute())
+}
+
+fn write_date_time(f: &mut dyn fmt::Write, seconds: i64) -> fmt::Result {
+    let (days, second_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    write!(
+        f,
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        second_of_day / 3600,
+        second_of_day / 60 % 60,
+        second_of_day % 60
+    )
+}
+
+/// Converts days since the unix epoch to a year, month and day in the
+/// proleptic Gregorian calendar.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u32, day as u32)
+}
+
+/// Fixed-size buffer for `write!`, failing once full.
+struct Buffer([u8; DATE_TIME_LEN], usize);
+
+impl fmt::Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.1 + s.len();
+        self.0
+            .get_mut(self.1..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.1 = end;
+        Ok(())
+    }
+}
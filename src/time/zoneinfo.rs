@@ -0,0 +1,596 @@
+//! Time zone rules, read from the system's zoneinfo database or POSIX `TZ`
+//! strings.
+//!
+//! See [RFC 8536] for the TZif format, and the [POSIX specification] for `TZ`.
+//!
+//! [RFC 8536]: https://www.rfc-editor.org/rfc/rfc8536
+//! [POSIX specification]: https://pubs.opengroup.org/onlinepubs/9699919799/basedefs/V1_chap08.html
+use std::{
+    env, fs,
+    path::Path,
+    ptr,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Once,
+    },
+};
+
+use super::civil_from_days;
+
+/// Zoneinfo database used when `TZDIR` isn't set.
+const DEFAULT_TZDIR: &str = "/usr/share/zoneinfo";
+
+/// Time zone rules as offsets east of UTC, in seconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Zone {
+    /// Offset before the first transition.
+    initial: i32,
+    /// Unix times at which the offset changes, and the offsets from then on,
+    /// sorted by time.
+    transitions: Vec<(i64, i32)>,
+    /// Rule for times after the last transition.
+    rule: Option<Rule>,
+}
+
+/// A POSIX `TZ` rule, such as `CET-1CEST,M3.5.0,M10.5.0/3`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    standard: i32,
+    dst: Option<Dst>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Dst {
+    offset: i32,
+    /// Start of daylight saving time, in standard time.
+    start: Change,
+    /// End of daylight saving time, in daylight saving time.
+    end: Change,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Change {
+    date: ChangeDate,
+    /// Seconds after midnight, which may be negative or exceed a day.
+    time: i32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ChangeDate {
+    /// `Jn`: day 1 to 365, never counting February 29th.
+    Julian(u16),
+    /// `n`: day 0 to 365, counting February 29th.
+    Ordinal(u16),
+    /// `Mm.w.d`: weekday `d` (0 is Sunday) of week `w` of month `m`, where week
+    /// 5 is the last.
+    MonthWeekDay { month: u8, week: u8, weekday: u8 },
+}
+
+impl Zone {
+    /// Rules for a zone always at the given offset.
+    pub fn fixed(offset: i32) -> Self {
+        Zone {
+            initial: offset,
+            transitions: Vec::new(),
+            rule: None,
+        }
+    }
+
+    /// Returns the offset in effect at the given unix time.
+    pub fn offset_at(&self, seconds: i64) -> i32 {
+        let index = self.transitions.partition_point(|&(at, _)| at <= seconds);
+        match (index, self.rule) {
+            (index, Some(rule)) if index == self.transitions.len() => rule.offset_at(seconds),
+            (0, _) => self.initial,
+            (index, _) => self.transitions[index - 1].1,
+        }
+    }
+}
+
+impl From<Rule> for Zone {
+    fn from(rule: Rule) -> Self {
+        Zone {
+            initial: rule.standard,
+            transitions: Vec::new(),
+            rule: Some(rule),
+        }
+    }
+}
+
+impl Rule {
+    fn offset_at(&self, seconds: i64) -> i32 {
+        let dst = match self.dst {
+            Some(dst) => dst,
+            None => return self.standard,
+        };
+        let (year, _, _) = civil_from_days((seconds + i64::from(self.standard)).div_euclid(86_400));
+        let start = dst.start.unix_time(year) - i64::from(self.standard);
+        let end = dst.end.unix_time(year) - i64::from(dst.offset);
+        let in_dst = if start <= end {
+            start <= seconds && seconds < end
+        } else {
+            // southern hemisphere: daylight saving time spans the new year.
+            !(end <= seconds && seconds < start)
+        };
+        if in_dst {
+            dst.offset
+        } else {
+            self.standard
+        }
+    }
+}
+
+impl Change {
+    /// Returns the local time of this change in the given year, as seconds
+    /// since the unix epoch.
+    fn unix_time(&self, year: i64) -> i64 {
+        let january_first = days_from_civil(year, 1, 1);
+        let day = match self.date {
+            ChangeDate::Julian(day) => {
+                january_first + i64::from(day) - 1 + i64::from(is_leap(year) && day >= 60)
+            }
+            ChangeDate::Ordinal(day) => january_first + i64::from(day),
+            ChangeDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let month = u32::from(month);
+                let first = days_from_civil(year, month, 1);
+                let next_month = match month {
+                    12 => days_from_civil(year + 1, 1, 1),
+                    _ => days_from_civil(year, month + 1, 1),
+                };
+                // 1970-01-01 was a Thursday.
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first
+                    + (i64::from(weekday) - first_weekday).rem_euclid(7)
+                    + 7 * (i64::from(week) - 1);
+                while day >= next_month {
+                    day -= 7;
+                }
+                day
+            }
+        };
+        day * 86_400 + i64::from(self.time)
+    }
+}
+
+/// Returns the rules for local time, loaded once per process.
+///
+/// `TZ` is consulted first: it may name a file in the zoneinfo database, give
+/// an absolute path to one, or hold a POSIX rule. Without `TZ`,
+/// `/etc/localtime` is read. When neither works, local time is UTC.
+pub fn local() -> &'static Zone {
+    static LOAD: Once = Once::new();
+    static LOCAL: AtomicPtr<Zone> = AtomicPtr::new(ptr::null_mut());
+
+    LOAD.call_once(|| {
+        let zone: &'static Zone =
+            Box::leak(Box::new(load_local().unwrap_or_else(|| Zone::fixed(0))));
+        LOCAL.store(zone as *const Zone as *mut Zone, Ordering::SeqCst);
+    });
+
+    // safe: set to a leaked, never-freed box above, and only read afterwards.
+    unsafe { &*LOCAL.load(Ordering::SeqCst) }
+}
+
+fn load_local() -> Option<Zone> {
+    let tz = match env::var_os("TZ") {
+        Some(tz) => tz,
+        None => return read_tzif(Path::new("/etc/localtime")),
+    };
+    let tz = tz.to_str()?;
+    let tz = tz.strip_prefix(':').unwrap_or(tz);
+    if tz.is_empty() {
+        return None;
+    }
+    if Path::new(tz).is_absolute() {
+        return read_tzif(Path::new(tz));
+    }
+    if !tz.split('/').any(|part| part == "..") {
+        let tzdir = env::var_os("TZDIR");
+        let tzdir = tzdir.as_deref().map_or(Path::new(DEFAULT_TZDIR), Path::new);
+        if let Some(zone) = read_tzif(&tzdir.join(tz)) {
+            return Some(zone);
+        }
+    }
+    parse_rule(tz).map(Zone::from)
+}
+
+fn read_tzif(path: &Path) -> Option<Zone> {
+    parse_tzif(&fs::read(path).ok()?)
+}
+
+/// Parses a TZif file. Leap seconds are ignored.
+pub fn parse_tzif(data: &[u8]) -> Option<Zone> {
+    let mut reader = Reader(data);
+    let mut header = reader.header()?;
+    let mut time_size = 4;
+    if header.version >= b'2' {
+        // skip the 32-bit data, and use the 64-bit data following it.
+        reader.take(header.data_len(time_size))?;
+        header = reader.header()?;
+        time_size = 8;
+    }
+
+    let times = reader.take(header.time_count * time_size)?;
+    let type_indices = reader.take(header.time_count)?;
+    let types = (0..header.type_count)
+        .map(|_| {
+            let offset = i32::from_be_bytes(reader.array()?);
+            // skip is_dst and the abbreviation index.
+            reader.take(2)?;
+            Some(offset)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let transitions = times
+        .chunks(time_size)
+        .zip(type_indices)
+        .map(|(time, &index)| {
+            let time = match *time {
+                [a, b, c, d] => i64::from(i32::from_be_bytes([a, b, c, d])),
+                _ => i64::from_be_bytes(time.try_into().ok()?),
+            };
+            Some((time, *types.get(usize::from(index))?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let rule = if header.version >= b'2' {
+        // skip abbreviations, leap seconds and indicators.
+        reader.take(header.data_len(time_size) - header.transitions_len(time_size))?;
+        let footer = reader.0.strip_prefix(b"\n")?;
+        let end = footer.iter().position(|&b| b == b'\n')?;
+        match &footer[..end] {
+            [] => None,
+            rule => Some(parse_rule(std::str::from_utf8(rule).ok()?)?),
+        }
+    } else {
+        None
+    };
+
+    Some(Zone {
+        initial: *types.first()?,
+        transitions,
+        rule,
+    })
+}
+
+struct Header {
+    version: u8,
+    is_ut_count: usize,
+    is_std_count: usize,
+    leap_count: usize,
+    time_count: usize,
+    type_count: usize,
+    char_count: usize,
+}
+
+impl Header {
+    /// Length of transition times, their type indices, and the types.
+    fn transitions_len(&self, time_size: usize) -> usize {
+        self.time_count * (time_size + 1) + self.type_count * 6
+    }
+
+    /// Length of all data following the header.
+    fn data_len(&self, time_size: usize) -> usize {
+        self.transitions_len(time_size)
+            + self.char_count
+            + self.leap_count * (time_size + 4)
+            + self.is_std_count
+            + self.is_ut_count
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.0.len() {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+
+    fn count(&mut self) -> Option<usize> {
+        usize::try_from(u32::from_be_bytes(self.array()?)).ok()
+    }
+
+    fn header(&mut self) -> Option<Header> {
+        if self.take(4)? != b"TZif" {
+            return None;
+        }
+        let version = self.take(1)?[0];
+        self.take(15)?;
+        Some(Header {
+            version,
+            is_ut_count: self.count()?,
+            is_std_count: self.count()?,
+            leap_count: self.count()?,
+            time_count: self.count()?,
+            type_count: self.count()?,
+            char_count: self.count()?,
+        })
+    }
+}
+
+/// Parses a POSIX `TZ` rule, such as `EST5EDT,M3.2.0,M11.1.0`.
+pub fn parse_rule(rule: &str) -> Option<Rule> {
+    let mut parser = Parser(rule.as_bytes());
+    parser.name()?;
+    // POSIX offsets are west of UTC.
+    let standard = -parser.time(24)?;
+    if parser.0.is_empty() {
+        return Some(Rule {
+            standard,
+            dst: None,
+        });
+    }
+
+    parser.name()?;
+    let offset = match parser.0.first() {
+        None | Some(b',') => standard + 3600,
+        Some(_) => -parser.time(24)?,
+    };
+    let (start, end) = if parser.eat(b',') {
+        let start = parser.change()?;
+        if !parser.eat(b',') {
+            return None;
+        }
+        (start, parser.change()?)
+    } else {
+        // the US rules, which POSIX leaves to the implementation.
+        let change = |month, week| Change {
+            date: ChangeDate::MonthWeekDay {
+                month,
+                week,
+                weekday: 0,
+            },
+            time: 7200,
+        };
+        (change(3, 2), change(11, 1))
+    };
+    if !parser.0.is_empty() {
+        return None;
+    }
+    Some(Rule {
+        standard,
+        dst: Some(Dst { offset, start, end }),
+    })
+}
+
+struct Parser<'a>(&'a [u8]);
+
+impl Parser<'_> {
+    fn eat(&mut self, byte: u8) -> bool {
+        match self.0.split_first() {
+            Some((&first, rest)) if first == byte => {
+                self.0 = rest;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn name(&mut self) -> Option<()> {
+        let len = if self.eat(b'<') {
+            let len = self.0.iter().position(|&b| b == b'>')?;
+            let name = &self.0[..len];
+            if !name
+                .iter()
+                .all(|&b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-')
+            {
+                return None;
+            }
+            self.0 = &self.0[len + 1..];
+            len
+        } else {
+            let len = self
+                .0
+                .iter()
+                .position(|b| !b.is_ascii_alphabetic())
+                .unwrap_or(self.0.len());
+            self.0 = &self.0[len..];
+            len
+        };
+        (len >= 3).then_some(())
+    }
+
+    fn number(&mut self, max: u32) -> Option<u32> {
+        let len = self
+            .0
+            .iter()
+            .position(|b| !b.is_ascii_digit())
+            .unwrap_or(self.0.len());
+        let (digits, rest) = self.0.split_at(len);
+        self.0 = rest;
+        let number = std::str::from_utf8(digits).ok()?.parse().ok()?;
+        (!digits.is_empty() && number <= max).then_some(number)
+    }
+
+    /// Parses `[+-]hh[:mm[:ss]]` into seconds.
+    fn time(&mut self, max_hours: u32) -> Option<i32> {
+        let negative = self.eat(b'-');
+        if !negative {
+            self.eat(b'+');
+        }
+        let mut seconds = self.number(max_hours)? * 3600;
+        if self.eat(b':') {
+            seconds += self.number(59)? * 60;
+            if self.eat(b':') {
+                seconds += self.number(59)?;
+            }
+        }
+        let seconds = i32::try_from(seconds).ok()?;
+        Some(if negative { -seconds } else { seconds })
+    }
+
+    fn change(&mut self) -> Option<Change> {
+        let date = if self.eat(b'J') {
+            ChangeDate::Julian(u16::try_from(self.number(365)?).ok().filter(|&d| d >= 1)?)
+        } else if self.eat(b'M') {
+            let month = self.number(12).filter(|&m| m >= 1)?;
+            let week = self.eat(b'.').then(|| self.number(5))??;
+            let weekday = self.eat(b'.').then(|| self.number(6))??;
+            ChangeDate::MonthWeekDay {
+                month: month as u8,
+                week: u8::try_from(week).ok().filter(|&w| w >= 1)?,
+                weekday: weekday as u8,
+            }
+        } else {
+            ChangeDate::Ordinal(u16::try_from(self.number(365)?).ok()?)
+        };
+        // RFC 8536 extends the time to -167 to 167 hours.
+        let time = if self.eat(b'/') {
+            self.time(167)?
+        } else {
+            7200
+        };
+        Some(Change { date, time })
+    }
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Converts a year, month and day in the proleptic Gregorian calendar to days
+/// since the unix epoch.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = i64::from(if month > 2 { month - 3 } else { month + 9 });
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Builds a version 2 TZif file with the given 64-bit transitions, types
+    /// and footer.
+    fn tzif(transitions: &[(i64, u8)], types: &[i32], footer: &str) -> Vec<u8> {
+        let header = |data: &mut Vec<u8>, times: usize, types: usize| {
+            data.extend_from_slice(b"TZif2");
+            data.extend_from_slice(&[0; 15]);
+            for count in [0, 0, 0, times, types, 4] {
+                data.extend_from_slice(&(count as u32).to_be_bytes());
+            }
+        };
+        let mut data = Vec::new();
+        // an empty 32-bit block, which should be skipped.
+        header(&mut data, 0, 1);
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(b"UTC\0");
+
+        header(&mut data, transitions.len(), types.len());
+        for &(time, _) in transitions {
+            data.extend_from_slice(&time.to_be_bytes());
+        }
+        for &(_, index) in transitions {
+            data.push(index);
+        }
+        for &offset in types {
+            data.extend_from_slice(&offset.to_be_bytes());
+            data.extend_from_slice(&[0, 0]);
+        }
+        data.extend_from_slice(b"ABC\0");
+        data.push(b'\n');
+        data.extend_from_slice(footer.as_bytes());
+        data.push(b'\n');
+        data
+    }
+
+    #[test]
+    fn test_parse_tzif() {
+        let data = tzif(&[(1000, 1), (2000, 0)], &[3600, 7200], "<+03>-3");
+        let zone = parse_tzif(&data).unwrap();
+        assert_eq!(zone.offset_at(0), 3600);
+        assert_eq!(zone.offset_at(1000), 7200);
+        assert_eq!(zone.offset_at(1999), 7200);
+        assert_eq!(zone.offset_at(2000), 10_800);
+
+        let zone = parse_tzif(&tzif(&[(1000, 1)], &[3600, 7200], "")).unwrap();
+        assert_eq!(zone.offset_at(5000), 7200);
+
+        assert_eq!(parse_tzif(b"TZif2"), None);
+        assert_eq!(parse_tzif(&data[..data.len() - 1]), None);
+        assert_eq!(parse_tzif(&tzif(&[(1000, 2)], &[0, 0], "")), None);
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(
+            parse_rule("UTC0"),
+            Some(Rule {
+                standard: 0,
+                dst: None
+            })
+        );
+        assert_eq!(parse_rule("<+0530>-5:30").unwrap().standard, 19_800);
+        let rule = parse_rule("EST5EDT").unwrap();
+        assert_eq!(
+            rule,
+            parse_rule("EST5EDT4,M3.2.0/2,M11.1.0/02:00:00").unwrap()
+        );
+        assert_eq!(rule.dst.unwrap().offset, -14_400);
+
+        for invalid in [
+            "",
+            "UT0",
+            "UTC",
+            "UTC0x",
+            "EST5EDT,M3.2.0",
+            "EST5EDT,M13.1.0,M1.1.0",
+        ] {
+            assert_eq!(parse_rule(invalid), None, "parsing {:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_rule_changes() {
+        // 2024-03-31T01:00:00Z and 2024-10-27T01:00:00Z.
+        let (start, end) = (1_711_846_800, 1_729_990_800);
+        let europe = parse_rule("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        assert_eq!(europe.offset_at(start - 1), 3600);
+        assert_eq!(europe.offset_at(start), 7200);
+        assert_eq!(europe.offset_at(end - 1), 7200);
+        assert_eq!(europe.offset_at(end), 3600);
+
+        // 2024-04-06T16:00:00Z and 2024-10-05T16:00:00Z.
+        let (end, start) = (1_712_419_200, 1_728_144_000);
+        let sydney = parse_rule("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!(sydney.offset_at(end - 1), 39_600);
+        assert_eq!(sydney.offset_at(end), 36_000);
+        assert_eq!(sydney.offset_at(start - 1), 36_000);
+        assert_eq!(sydney.offset_at(start), 39_600);
+
+        // day 60 is March 1st with `J`, and February 29th without.
+        let change = |date| Change { date, time: 0 };
+        let leap_day = days_from_civil(2024, 2, 29) * 86_400;
+        assert_eq!(
+            change(ChangeDate::Julian(60)).unix_time(2024),
+            leap_day + 86_400
+        );
+        assert_eq!(change(ChangeDate::Ordinal(59)).unix_time(2024), leap_day);
+    }
+
+    #[test]
+    fn test_days_from_civil() {
+        for days in [-719_468, -1, 0, 11_016, 19_782, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
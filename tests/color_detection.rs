@@ -0,0 +1,89 @@
+//! Tests for deciding whether to write colors per output.
+#![cfg(feature = "colored")]
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use fern::{colors::ColoredLevelConfig, testing::Capture, MemoryBuffer};
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+#[derive(Clone, Default)]
+struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_colors_decided_per_output() {
+    let colors = ColoredLevelConfig::new();
+    let capture = Capture::new();
+    let buffer = MemoryBuffer::new(10);
+    let writer = SharedWriter::default();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .format(move |out, message, record| {
+            out.finish(format_args!(
+                "[{}] {}",
+                colors.color(record.level()),
+                message
+            ))
+        })
+        // outputs which aren't terminals, including custom loggers, don't
+        // write colors.
+        .chain(capture.clone())
+        .chain(buffer.clone())
+        .chain(fern::Dispatch::new().chain(Box::new(writer.clone()) as Box<dyn io::Write + Send>))
+        .into_log();
+
+    manual_log(&*logger, Error, "message");
+
+    assert_eq!(capture.records()[0].message(), "[ERROR] message");
+    assert_eq!(buffer.snapshot()[0].message(), "[ERROR] message");
+    assert_eq!(
+        String::from_utf8(writer.0.lock().unwrap().clone()).unwrap(),
+        "[ERROR] message\n"
+    );
+
+    // outside of logging, colors are enabled.
+    assert!(fern::colors::enabled());
+}
+
+#[test]
+fn test_no_colors_rendered_once() {
+    let colors = ColoredLevelConfig::new();
+    let capture = Capture::new();
+    let writer = SharedWriter::default();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .render_once(true)
+        .format(move |out, message, record| {
+            out.finish(format_args!(
+                "[{}] {}",
+                colors.color(record.level()),
+                message
+            ))
+        })
+        .chain(capture.clone())
+        .chain(Box::new(writer.clone()) as Box<dyn io::Write + Send>)
+        .into_log();
+
+    manual_log(&*logger, Error, "message");
+
+    assert_eq!(capture.records()[0].message(), "[ERROR] message");
+    assert_eq!(
+        String::from_utf8(writer.0.lock().unwrap().clone()).unwrap(),
+        "[ERROR] message\n"
+    );
+}
@@ -0,0 +1,130 @@
+//! Tests for thread-local context key-values.
+use fern::testing::Capture;
+
+#[test]
+fn test_context_get() {
+    assert_eq!(fern::context::get("request_id"), None);
+    {
+        let _request = fern::context::push("request_id", 1);
+        let _user = fern::context::push("user_id", "alice");
+        assert_eq!(fern::context::get("request_id").as_deref(), Some("1"));
+        {
+            let _inner = fern::context::push("request_id", 2);
+            assert_eq!(fern::context::get("request_id").as_deref(), Some("2"));
+
+            let mut pairs = Vec::new();
+            fern::context::for_each(|k, v| pairs.push(format!("{}={}", k, v)));
+            assert_eq!(pairs, ["request_id=1", "user_id=alice", "request_id=2"]);
+        }
+        assert_eq!(fern::context::get("request_id").as_deref(), Some("1"));
+    }
+    assert_eq!(fern::context::get("request_id"), None);
+    assert_eq!(fern::context::get("user_id"), None);
+}
+
+#[cfg(feature = "kv")]
+#[test]
+fn test_context_key_values() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .format(
+            |out, message, record| match record.key_values().get("request_id".into()) {
+                Some(request_id) => out.finish(format_args!("[{}] {}", request_id, message)),
+                None => out.finish(format_args!("{}", message)),
+            },
+        )
+        // nested dispatches shouldn't add the context again.
+        .chain(fern::Dispatch::new().chain(capture.clone()))
+        .into_log();
+
+    let log = |message: &str| {
+        let kvs = [("user_id", "bob")];
+        logger.log(
+            &log::RecordBuilder::new()
+                .args(format_args!("{}", message))
+                .level(log::Level::Info)
+                .key_values(&kvs)
+                .build(),
+        )
+    };
+
+    log("no context");
+    {
+        let _request = fern::context::push("request_id", 42);
+        let _user = fern::context::push("user_id", "alice");
+        let _tenant = fern::context::push("tenant", "acme");
+        let _inner = fern::context::push("tenant", "initech");
+        log("with context");
+    }
+
+    let records = capture.records();
+    assert_eq!(records[0].message(), "no context");
+    assert_eq!(records[0].key_values().len(), 1);
+
+    assert_eq!(records[1].message(), "[42] with context");
+    let key_values: Vec<_> = records[1]
+        .key_values()
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    // the record's own user_id shadows the context's.
+    assert_eq!(
+        key_values,
+        ["user_id=bob", "tenant=initech", "request_id=42"]
+    );
+}
+
+#[cfg(feature = "kv")]
+#[test]
+fn test_context_pushed_while_logging() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .format(|out, message, record| {
+            // the record keeps the context it was logged with.
+            let _step = fern::context::push("step", "format");
+            let step = fern::context::get("step").unwrap_or_default();
+            let key_values = record.key_values().count();
+            out.finish(format_args!("{} {} {}", step, key_values, message))
+        })
+        .chain(capture.clone())
+        .into_log();
+
+    let _request = fern::context::push("request_id", 42);
+    logger.log(
+        &log::RecordBuilder::new()
+            .args(format_args!("hello"))
+            .level(log::Level::Info)
+            .build(),
+    );
+
+    assert_eq!(capture.records()[0].message(), "format 1 hello");
+    let mut pairs = Vec::new();
+    fern::context::for_each(|k, v| pairs.push(format!("{}={}", k, v)));
+    assert_eq!(pairs, ["request_id=42"]);
+}
+
+#[cfg(not(feature = "kv"))]
+#[test]
+fn test_context_without_kv() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .format(|out, message, _| {
+            let request_id = fern::context::get("request_id").unwrap_or_default();
+            out.finish(format_args!("[{}] {}", request_id, message))
+        })
+        .chain(capture.clone())
+        .into_log();
+
+    let _request = fern::context::push("request_id", 42);
+    logger.log(
+        &log::RecordBuilder::new()
+            .args(format_args!("hello"))
+            .level(log::Level::Info)
+            .build(),
+    );
+
+    assert_eq!(capture.records()[0].message(), "[42] hello");
+}
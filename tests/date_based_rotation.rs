@@ -0,0 +1,179 @@
+//! Tests for rotating `DateBased` log files as time passes.
+#![cfg(feature = "date-based")]
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use fern::time::ManualClock;
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+fn at(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+/// Returns each file in the directory with its contents, sorted by name.
+fn log_files(dir: &Path) -> Vec<(String, String)> {
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .expect("Failed to read log directory")
+        .map(|entry| {
+            let entry = entry.unwrap();
+            (
+                entry.file_name().into_string().unwrap(),
+                fs::read_to_string(entry.path()).unwrap(),
+            )
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn owned(files: &[(&str, &str)]) -> Vec<(String, String)> {
+    files
+        .iter()
+        .map(|&(name, contents)| (name.to_owned(), contents.to_owned()))
+        .collect()
+}
+
+#[test]
+fn test_rotation_across_hours() {
+    let temp_log_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    // 2024-01-01T09:59:58Z
+    let clock = ManualClock::new(at(1_704_103_198));
+
+    {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .chain(
+                fern::DateBased::new(temp_log_dir.path().join("app."), "%Y-%m-%dT%H.log")
+                    .utc_time()
+                    .clock(clock.clone()),
+            )
+            .into_log();
+
+        manual_log(&*logger, Info, "first");
+        clock.advance(Duration::from_secs(1));
+        manual_log(&*logger, Info, "same hour");
+        clock.advance(Duration::from_secs(1));
+        manual_log(&*logger, Info, "next hour");
+        clock.advance(Duration::from_secs(3600));
+        manual_log(&*logger, Info, "hour after");
+        logger.flush();
+    }
+
+    assert_eq!(
+        log_files(temp_log_dir.path()),
+        owned(&[
+            ("app.2024-01-01T09.log", "first\nsame hour\n"),
+            ("app.2024-01-01T10.log", "next hour\n"),
+            ("app.2024-01-01T11.log", "hour after\n"),
+        ])
+    );
+
+    temp_log_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
+
+#[test]
+fn test_rotation_across_days() {
+    let temp_log_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    // 2024-02-28T23:59:59Z, before a leap day.
+    let clock = ManualClock::new(at(1_709_164_799));
+
+    {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .chain(
+                fern::DateBased::new(temp_log_dir.path().join("app."), "%Y-%m-%d.log")
+                    .utc_time()
+                    .clock(clock.clone()),
+            )
+            .into_log();
+
+        manual_log(&*logger, Info, "february 28th");
+        clock.advance(Duration::from_secs(1));
+        manual_log(&*logger, Info, "february 29th");
+        clock.advance(Duration::from_secs(86_400));
+        manual_log(&*logger, Info, "march 1st");
+        // going back in time reopens the earlier file.
+        clock.set(at(1_709_164_799));
+        manual_log(&*logger, Info, "february 28th again");
+        logger.flush();
+    }
+
+    assert_eq!(
+        log_files(temp_log_dir.path()),
+        owned(&[
+            ("app.2024-02-28.log", "february 28th\nfebruary 28th again\n"),
+            ("app.2024-02-29.log", "february 29th\n"),
+            ("app.2024-03-01.log", "march 1st\n"),
+        ])
+    );
+
+    temp_log_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
+
+#[cfg(feature = "timezone")]
+#[test]
+fn test_rotation_across_dst_changes() {
+    let temp_log_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    let berlin = fern::time::Timezone::posix("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+    // an hour before daylight saving time starts at 2024-03-31T01:00:00Z.
+    let clock = ManualClock::new(at(1_711_843_200));
+
+    {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .chain(
+                fern::DateBased::new(temp_log_dir.path().join("hourly."), "%Y-%m-%dT%H.log")
+                    .timezone(berlin.clone())
+                    .clock(clock.clone()),
+            )
+            .chain(
+                fern::DateBased::new(temp_log_dir.path().join("offset."), "%Y-%m-%dT%H%z.log")
+                    .timezone(berlin)
+                    .clock(clock.clone()),
+            )
+            .into_log();
+
+        // 01:00 CET, then 03:00 CEST: there is no 02:00.
+        manual_log(&*logger, Info, "before spring");
+        clock.advance(Duration::from_secs(3600));
+        manual_log(&*logger, Info, "after spring");
+
+        // 02:00 CEST, then 02:00 CET again an hour later, as daylight saving
+        // time ends at 2024-10-27T01:00:00Z.
+        clock.set(at(1_729_987_200));
+        manual_log(&*logger, Info, "before autumn");
+        clock.advance(Duration::from_secs(3600));
+        manual_log(&*logger, Info, "after autumn");
+        clock.advance(Duration::from_secs(3600));
+        manual_log(&*logger, Info, "after that");
+        logger.flush();
+    }
+
+    assert_eq!(
+        log_files(temp_log_dir.path()),
+        owned(&[
+            ("hourly.2024-03-31T01.log", "before spring\n"),
+            ("hourly.2024-03-31T03.log", "after spring\n"),
+            // the repeated hour shares a file, unless named with the offset.
+            ("hourly.2024-10-27T02.log", "before autumn\nafter autumn\n"),
+            ("hourly.2024-10-27T03.log", "after that\n"),
+            ("offset.2024-03-31T01+0100.log", "before spring\n"),
+            ("offset.2024-03-31T03+0200.log", "after spring\n"),
+            ("offset.2024-10-27T02+0100.log", "after autumn\n"),
+            ("offset.2024-10-27T02+0200.log", "before autumn\n"),
+            ("offset.2024-10-27T03+0100.log", "after that\n"),
+        ])
+    );
+
+    temp_log_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
@@ -0,0 +1,173 @@
+//! Tests for fields added to every record by a dispatch.
+#![cfg(feature = "kv")]
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use fern::testing::Capture;
+use log::{Level::*, Log};
+
+mod support;
+
+use support::manual_log;
+
+fn key_values(capture: &Capture) -> Vec<Vec<String>> {
+    capture
+        .records()
+        .iter()
+        .map(|record| {
+            record
+                .key_values()
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_dispatch_fields() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    let outer = Capture::new();
+    let inner = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .with_field("service", "billing")
+        .with_field("pid", 1234)
+        .with_field("service", "payments")
+        .with_fields_from(|| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            vec![("host", "example")]
+        })
+        .format(|out, message, record| {
+            let service = record.key_values().get("service".into()).unwrap();
+            out.finish(format_args!("[{}] {}", service, message))
+        })
+        .chain(outer.clone())
+        .chain(
+            fern::Dispatch::new()
+                .with_field("output", "inner")
+                .chain(inner.clone()),
+        )
+        .into_log();
+
+    assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+    for message in ["one", "two"] {
+        let kvs = [("request_id", 7)];
+        logger.log(
+            &log::RecordBuilder::new()
+                .args(format_args!("{}", message))
+                .level(log::Level::Info)
+                .key_values(&kvs)
+                .build(),
+        );
+    }
+
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    assert_eq!(outer.records()[1].message(), "[payments] two");
+    assert_eq!(
+        key_values(&outer)[1],
+        [
+            "request_id=7",
+            "host=example",
+            "pid=1234",
+            "service=payments"
+        ]
+    );
+    assert_eq!(
+        key_values(&inner)[0],
+        [
+            "request_id=7",
+            "host=example",
+            "pid=1234",
+            "service=payments",
+            "output=inner"
+        ]
+    );
+}
+
+#[test]
+fn test_computed_fields_on_every_thread() {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+
+    let capture = Capture::new();
+    let (_max_level, logger) = fern::Dispatch::new()
+        .with_fields_from(|| {
+            STARTED.store(true, Ordering::SeqCst);
+            // give other threads time to log while this is computed.
+            thread::sleep(Duration::from_millis(100));
+            vec![("host", "example")]
+        })
+        .chain(capture.clone())
+        .into_log();
+    let logger: Arc<dyn Log> = Arc::from(logger);
+
+    let first = {
+        let logger = logger.clone();
+        thread::spawn(move || manual_log(&*logger, Info, "first"))
+    };
+    while !STARTED.load(Ordering::SeqCst) {
+        thread::yield_now();
+    }
+    manual_log(&*logger, Info, "second");
+    first.join().unwrap();
+
+    assert_eq!(key_values(&capture), [["host=example"], ["host=example"]]);
+}
+
+#[test]
+fn test_logging_while_computing_fields() {
+    let capture = Capture::new();
+    let slot: Arc<Mutex<Option<Arc<dyn Log>>>> = Arc::default();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .with_fields_from({
+            let slot = slot.clone();
+            move || {
+                let logger = slot.lock().unwrap().clone();
+                if let Some(logger) = logger {
+                    manual_log(&*logger, Info, "computing");
+                }
+                vec![("host", "example")]
+            }
+        })
+        .chain(capture.clone())
+        .into_log();
+    let logger: Arc<dyn Log> = Arc::from(logger);
+    *slot.lock().unwrap() = Some(logger.clone());
+
+    manual_log(&*logger, Info, "message");
+    // break the cycle between the logger and its fields function.
+    slot.lock().unwrap().take();
+
+    assert_eq!(capture.messages(), ["computing", "message"]);
+    assert_eq!(key_values(&capture), [vec![], vec!["host=example"]]);
+}
+
+#[test]
+fn test_record_key_values_shadow_fields() {
+    let capture = Capture::new();
+    let (_max_level, logger) = fern::Dispatch::new()
+        .with_field("user", "anonymous")
+        .with_field("service", "billing")
+        .chain(capture.clone())
+        .into_log();
+
+    let kvs = [("user", "alice")];
+    logger.log(
+        &log::RecordBuilder::new()
+            .args(format_args!("login"))
+            .level(Info)
+            .key_values(&kvs)
+            .build(),
+    );
+
+    assert_eq!(key_values(&capture), [["user=alice", "service=billing"]]);
+}
@@ -0,0 +1,55 @@
+//! Tests for composing formatting stages.
+use std::fmt;
+
+use fern::{testing::Capture, FormatCallback};
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+fn with_level(out: FormatCallback, message: &fmt::Arguments, record: &log::Record) {
+    out.finish(format_args!("[{}] {}", record.level(), message))
+}
+
+#[test]
+fn test_format_stages_run_in_order() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .format(|out, message, record| {
+            // later stages see the previous stage's output as args()
+            assert_eq!(format!("{}", message), format!("{}", record.args()));
+            out.finish(format_args!("<{}>", message))
+        })
+        .then_format(with_level)
+        .then_format(|_out, _message, _record| {
+            // not calling finish passes the message on unchanged
+        })
+        .then_format(|out, message, record| {
+            assert_eq!(format!("{}", record.args()), format!("{}", message));
+            out.finish(format_args!("{}!", message))
+        })
+        .chain(capture.clone())
+        .into_log();
+
+    manual_log(&*logger, Info, "hello");
+
+    assert_eq!(capture.messages(), ["[INFO] <hello>!"]);
+}
+
+#[test]
+fn test_format_replaces_stages() {
+    let capture = Capture::new();
+    let shared = capture.clone();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .then_format(with_level)
+        .format(|out, message, _| out.finish(format_args!("<{}>", message)))
+        .chain(fern::Dispatch::new().then_format(with_level).chain(shared))
+        .into_log();
+
+    manual_log(&*logger, Warn, "hello");
+
+    assert_eq!(capture.messages(), ["[WARN] <hello>"]);
+}
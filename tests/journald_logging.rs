@@ -0,0 +1,168 @@
+//! Tests for the systemd journal output, using a socket standing in for
+//! journald.
+#![cfg(all(target_os = "linux", feature = "journald"))]
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom},
+    os::unix::{
+        io::{AsRawFd, FromRawFd},
+        net::UnixDatagram,
+    },
+};
+
+use log::Level::*;
+
+/// Parses a native protocol payload into (name, value) pairs.
+fn parse_fields(mut payload: &[u8]) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    while !payload.is_empty() {
+        let line_end = payload.iter().position(|&b| b == b'\n').unwrap();
+        let line = &payload[..line_end];
+        if let Some(eq) = line.iter().position(|&b| b == b'=') {
+            fields.push((
+                String::from_utf8(line[..eq].to_vec()).unwrap(),
+                String::from_utf8(line[eq + 1..].to_vec()).unwrap(),
+            ));
+            payload = &payload[line_end + 1..];
+        } else {
+            let name = String::from_utf8(line.to_vec()).unwrap();
+            let rest = &payload[line_end + 1..];
+            let mut len = [0; 8];
+            len.copy_from_slice(&rest[..8]);
+            let len = u64::from_le_bytes(len) as usize;
+            let value = String::from_utf8(rest[8..8 + len].to_vec()).unwrap();
+            assert_eq!(rest[8 + len], b'\n');
+            fields.push((name, value));
+            payload = &rest[8 + len + 1..];
+        }
+    }
+    fields
+}
+
+fn field<'a>(fields: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    fields
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+#[test]
+fn test_journald_fields() {
+    let temp_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    let socket_path = temp_dir.path().join("journal.sock");
+    let socket = UnixDatagram::bind(&socket_path).unwrap();
+
+    {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .format(|out, msg, record| out.finish(format_args!("[{}] {}", record.level(), msg)))
+            .chain(
+                fern::Journald::new()
+                    .socket_path(&socket_path)
+                    .syslog_identifier("fern-test"),
+            )
+            .into_log();
+
+        let kvs = [
+            ("request_id", 42),
+            ("user.id", 7),
+            ("message", 1),
+            ("code_line", 2),
+            ("_pid", 3),
+        ];
+        logger.log(
+            &log::RecordBuilder::new()
+                .args(format_args!("line one\nline two"))
+                .level(Warn)
+                .target("my_app::db")
+                .file(Some("src/db.rs"))
+                .line(Some(12))
+                .module_path(Some("my_app::db"))
+                .key_values(&kvs)
+                .build(),
+        );
+
+        let mut buf = vec![0; 4096];
+        let len = socket.recv(&mut buf).unwrap();
+        let fields = parse_fields(&buf[..len]);
+
+        assert_eq!(field(&fields, "MESSAGE"), Some("[WARN] line one\nline two"));
+        assert_eq!(field(&fields, "PRIORITY"), Some("4"));
+        assert_eq!(field(&fields, "TARGET"), Some("my_app::db"));
+        assert_eq!(field(&fields, "CODE_FILE"), Some("src/db.rs"));
+        assert_eq!(field(&fields, "CODE_LINE"), Some("12"));
+        assert_eq!(field(&fields, "CODE_MODULE"), Some("my_app::db"));
+        assert_eq!(field(&fields, "THREAD"), Some("test_journald_fields"));
+        assert_eq!(field(&fields, "SYSLOG_IDENTIFIER"), Some("fern-test"));
+        assert_eq!(field(&fields, "REQUEST_ID"), Some("42"));
+        assert_eq!(field(&fields, "USER_ID"), Some("7"));
+        // key-values can't overwrite fields set by fern, or journald.
+        assert_eq!(field(&fields, "KV_MESSAGE"), Some("1"));
+        assert_eq!(field(&fields, "KV_CODE_LINE"), Some("2"));
+        let names: Vec<_> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names.iter().filter(|&&name| name == "MESSAGE").count(), 1);
+        assert_eq!(names.iter().filter(|&&name| name == "CODE_LINE").count(), 1);
+        assert!(!names.contains(&"PID"));
+        assert!(!names.contains(&"_PID"));
+    }
+
+    temp_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
+
+#[test]
+fn test_journald_large_payload_uses_memfd() {
+    let temp_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    let socket_path = temp_dir.path().join("journal.sock");
+    let socket = UnixDatagram::bind(&socket_path).unwrap();
+
+    {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .chain(fern::Journald::new().socket_path(&socket_path))
+            .into_log();
+
+        // far larger than the maximum datagram size.
+        let message = "x".repeat(4 * 1024 * 1024);
+        logger.log(
+            &log::RecordBuilder::new()
+                .args(format_args!("{}", message))
+                .level(Info)
+                .build(),
+        );
+
+        // receive the file descriptor passed with the (empty) datagram.
+        let mut data = [0u8; 16];
+        let mut iov = libc::iovec {
+            iov_base: data.as_mut_ptr() as *mut libc::c_void,
+            iov_len: data.len(),
+        };
+        let mut cmsg_buf = [0u64; 8];
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = cmsg_buf.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = std::mem::size_of_val(&cmsg_buf) as _;
+
+        let received = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, 0) };
+        assert_eq!(received, 0, "expected an empty datagram");
+
+        let fd = unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            assert!(!cmsg.is_null(), "expected a file descriptor");
+            assert_eq!((*cmsg).cmsg_type, libc::SCM_RIGHTS);
+            std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const std::os::raw::c_int)
+        };
+        let mut memfd = unsafe { fs::File::from_raw_fd(fd) };
+        memfd.seek(SeekFrom::Start(0)).unwrap();
+        let mut payload = Vec::new();
+        memfd.read_to_end(&mut payload).unwrap();
+
+        let fields = parse_fields(&payload);
+        assert_eq!(field(&fields, "MESSAGE"), Some(&*message));
+        assert_eq!(field(&fields, "PRIORITY"), Some("6"));
+    }
+
+    temp_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
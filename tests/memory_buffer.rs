@@ -0,0 +1,101 @@
+//! Tests for the in-memory ring buffer output.
+use std::time::{Duration, UNIX_EPOCH};
+
+use fern::{time::ManualClock, MemoryBuffer};
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+#[test]
+fn test_memory_buffer_keeps_last_records() {
+    let buffer = MemoryBuffer::new(3);
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .format(|out, msg, record| out.finish(format_args!("[{}] {}", record.level(), msg)))
+        .chain(buffer.clone())
+        .into_log();
+
+    let l = &*logger;
+    manual_log(l, Info, "one");
+    manual_log(l, Warn, "two");
+    manual_log(l, Debug, "three");
+    manual_log(l, Error, "four");
+
+    assert_eq!(buffer.len(), 3);
+    let messages: Vec<_> = buffer
+        .snapshot()
+        .into_iter()
+        .map(|r| r.into_message())
+        .collect();
+    assert_eq!(messages, ["[WARN] two", "[DEBUG] three", "[ERROR] four"]);
+
+    let records = buffer.snapshot();
+    assert_eq!(records[0].level(), Warn);
+    assert!(records[0].timestamp() <= records[2].timestamp());
+}
+
+#[test]
+fn test_memory_buffer_filters_and_drains() {
+    let buffer = MemoryBuffer::new(10);
+
+    let (_max_level, logger) = fern::Dispatch::new().chain(buffer.clone()).into_log();
+
+    for (level, target, message) in [
+        (Info, "app", "started"),
+        (Warn, "app::db", "slow query"),
+        (Error, "apple", "bad fruit"),
+        (Debug, "app::db::pool", "checked out"),
+    ] {
+        logger.log(
+            &log::RecordBuilder::new()
+                .args(format_args!("{}", message))
+                .level(level)
+                .target(target)
+                .build(),
+        );
+    }
+
+    let warnings: Vec<_> = buffer
+        .snapshot_level(log::LevelFilter::Warn)
+        .into_iter()
+        .map(|r| r.into_message())
+        .collect();
+    assert_eq!(warnings, ["slow query", "bad fruit"]);
+
+    let app: Vec<_> = buffer
+        .snapshot_target("app")
+        .into_iter()
+        .map(|r| r.into_message())
+        .collect();
+    assert_eq!(app, ["started", "slow query", "checked out"]);
+
+    let db = buffer.snapshot_target("app::db");
+    assert_eq!(db.len(), 2);
+    assert_eq!(db[1].target(), "app::db::pool");
+
+    let drained = buffer.drain();
+    assert_eq!(drained.len(), 4);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_memory_buffer_clock() {
+    let clock = ManualClock::new(UNIX_EPOCH);
+    let buffer = MemoryBuffer::with_clock(10, clock.clone());
+
+    // every clone timestamps records with the same clock.
+    let (_max_level, logger) = fern::Dispatch::new()
+        .chain(buffer.clone())
+        .chain(buffer.clone())
+        .into_log();
+
+    manual_log(&*logger, Info, "first");
+    clock.advance(Duration::from_secs(5));
+    manual_log(&*logger, Info, "second");
+
+    let timestamps: Vec<_> = buffer.snapshot().iter().map(|r| r.timestamp()).collect();
+    let later = UNIX_EPOCH + Duration::from_secs(5);
+    assert_eq!(timestamps, [UNIX_EPOCH, UNIX_EPOCH, later, later]);
+}
@@ -0,0 +1,78 @@
+//! Tests for truncating long messages.
+use fern::{testing::Capture, Output, Redactor};
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+#[test]
+fn test_max_message_len() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .max_message_len(10)
+        .format(|out, message, _| out.finish(format_args!("> {}", message)))
+        .chain(capture.clone())
+        .into_log();
+
+    manual_log(&*logger, Info, "short");
+    manual_log(&*logger, Info, "12345678");
+    manual_log(&*logger, Info, "0123456789abcdef");
+    // 'é' is two bytes, and can't be split.
+    manual_log(&*logger, Info, "1234567é89");
+    manual_log(
+        &*logger,
+        Info,
+        format_args!("{}{}{:?}", "12345", "6789", "x".repeat(100)),
+    );
+
+    assert_eq!(
+        capture.messages(),
+        [
+            "> short",
+            "> 12345678",
+            "> 01234567\u{2026}[truncated 8 bytes]",
+            "> 1234567\u{2026}[truncated 4 bytes]",
+            "> 12345678\u{2026}[truncated 103 bytes]",
+        ]
+    );
+}
+
+#[test]
+fn test_output_max_message_len() {
+    let full = Capture::new();
+    let truncated = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .chain(full.clone())
+        .chain(Output::max_message_len(truncated.clone(), 4))
+        .into_log();
+
+    manual_log(&*logger, Warn, "abcdefgh");
+
+    assert_eq!(full.records()[0].message(), "abcdefgh");
+    assert_eq!(
+        truncated.records()[0].message(),
+        "abcd\u{2026}[truncated 4 bytes]"
+    );
+}
+
+#[test]
+fn test_redacts_before_truncating() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .redact(Redactor::new())
+        .max_message_len(16)
+        .chain(capture.clone())
+        .into_log();
+
+    // the limit falls inside the card number.
+    manual_log(&*logger, Info, "paid with 4111 1111 1111 1111 today");
+
+    assert_eq!(
+        capture.records()[0].message(),
+        "paid with [REDAC\u{2026}[truncated 10 bytes]"
+    );
+}
@@ -0,0 +1,114 @@
+//! Tests for multiline message policies.
+use fern::{testing::Capture, Multiline, Output};
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+fn header(out: fern::FormatCallback, message: &std::fmt::Arguments, record: &log::Record) {
+    out.finish(format_args!("[{}] {}", record.level(), message))
+}
+
+#[test]
+fn test_multiline_policies() {
+    let indented = Capture::new();
+    let escaped = Capture::new();
+    let repeated = Capture::new();
+    let unchanged = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .chain(Output::multiline(
+            Output::formatted(indented.clone(), header),
+            Multiline::Indent("  | ".into()),
+        ))
+        .chain(Output::multiline(
+            Output::formatted(escaped.clone(), header),
+            Multiline::Escape,
+        ))
+        .chain(Output::multiline(
+            Output::formatted(repeated.clone(), header),
+            Multiline::RepeatHeader,
+        ))
+        .chain(Output::formatted(unchanged.clone(), header))
+        .into_log();
+
+    manual_log(&*logger, Error, "first\nsecond\r\n\nfourth\n");
+    manual_log(&*logger, Info, "single line");
+
+    assert_eq!(
+        indented.messages(),
+        [
+            "[ERROR] first\n  | second\n  | \n  | fourth",
+            "[INFO] single line"
+        ]
+    );
+    assert_eq!(
+        escaped.messages(),
+        [
+            "[ERROR] first\\nsecond\\r\\n\\nfourth",
+            "[INFO] single line"
+        ]
+    );
+    assert_eq!(
+        repeated.messages(),
+        [
+            "[ERROR] first",
+            "[ERROR] second",
+            "[ERROR] ",
+            "[ERROR] fourth",
+            "[INFO] single line"
+        ]
+    );
+    assert_eq!(
+        unchanged.messages(),
+        ["[ERROR] first\nsecond\r\n\nfourth\n", "[INFO] single line"]
+    );
+}
+
+#[test]
+fn test_escape_backslashes() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .multiline(Multiline::Escape)
+        .chain(capture.clone())
+        .into_log();
+
+    // a literal backslash-n, then a newline.
+    manual_log(&*logger, Info, "C:\\new\\\nnext");
+
+    assert_eq!(capture.messages(), [r"C:\\new\\\nnext"]);
+}
+
+#[test]
+fn test_escape_single_line() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .multiline(Multiline::Escape)
+        .chain(capture.clone())
+        .into_log();
+
+    // no newlines, but a literal backslash-n still needs escaping to be
+    // told apart from an escaped newline.
+    manual_log(&*logger, Info, r"C:\new");
+    manual_log(&*logger, Info, r"first\nsecond");
+
+    assert_eq!(capture.messages(), [r"C:\\new", r"first\\nsecond"]);
+}
+
+#[test]
+fn test_dispatch_multiline() {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .multiline(Multiline::RepeatHeader)
+        .format(header)
+        .chain(capture.clone())
+        .into_log();
+
+    manual_log(&*logger, Warn, "one\ntwo");
+
+    assert_eq!(capture.messages(), ["[WARN] one", "[WARN] two"]);
+}
@@ -0,0 +1,46 @@
+//! Tests for per-output formatting.
+use fern::testing::Capture;
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+#[test]
+fn test_output_formatted() {
+    let plain = Capture::new();
+    let json = Capture::new();
+    let (send, recv) = std::sync::mpsc::channel();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .level(log::LevelFilter::Info)
+        .format(|out, message, record| out.finish(format_args!("[{}] {}", record.level(), message)))
+        .chain(plain.clone())
+        .chain(fern::Output::formatted(
+            json.clone(),
+            |out, message, record| {
+                out.finish(format_args!(
+                    r#"{{"target":"{}","message":"{}"}}"#,
+                    record.target(),
+                    message
+                ))
+            },
+        ))
+        .chain(fern::Output::formatted(
+            fern::Output::sender(send, ""),
+            |out, message, _| out.finish(format_args!("{}\r\n", message)),
+        ))
+        .into_log();
+
+    manual_log(&*logger, Debug, "filtered");
+    manual_log(&*logger, Info, "hello");
+
+    assert_eq!(plain.records()[0].message(), "[INFO] hello");
+    assert_eq!(
+        json.records()[0].message(),
+        r#"{"target":"","message":"[INFO] hello"}"#
+    );
+    assert_eq!(json.records().len(), 1);
+    assert_eq!(recv.try_recv().unwrap(), "[INFO] hello\r\n");
+    assert!(recv.try_recv().is_err());
+}
@@ -0,0 +1,39 @@
+//! Tests for logging panics through the panic hook.
+use std::{panic, thread};
+
+#[test]
+fn test_panic_hook_logs_panics() {
+    let buffer = fern::MemoryBuffer::new(10);
+    fern::Dispatch::new()
+        .chain(buffer.clone())
+        .apply()
+        .expect("Failed to set global logger");
+
+    fern::install_panic_hook();
+
+    let line = line!() + 3;
+    let result = thread::Builder::new()
+        .name("worker".to_owned())
+        .spawn(|| panic!("something {} happened", "bad"))
+        .unwrap()
+        .join();
+    assert!(result.is_err());
+
+    let result = panic::catch_unwind(|| panic!("static message"));
+    assert!(result.is_err());
+
+    let records = buffer.snapshot_target("panic");
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].level(), log::Level::Error);
+    assert!(
+        records[0].message().starts_with(&format!(
+            "thread 'worker' panicked at {}:{}:",
+            file!(),
+            line
+        )),
+        "unexpected message: {}",
+        records[0].message()
+    );
+    assert!(records[0].message().contains(":\nsomething bad happened"));
+    assert!(records[1].message().contains(":\nstatic message"));
+}
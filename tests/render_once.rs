@@ -0,0 +1,126 @@
+//! Tests for rendering messages once for all outputs.
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+};
+
+use fern::testing::Capture;
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+struct Counted<'a>(&'a AtomicUsize);
+
+impl fmt::Display for Counted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        write!(f, "expensive")
+    }
+}
+
+#[derive(Clone, Default)]
+struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn log_counted(render_once: bool) -> (usize, Vec<String>) {
+    let capture = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .render_once(render_once)
+        .format(|out, message, record| out.finish(format_args!("[{}] {}", record.level(), message)))
+        .chain(capture.clone())
+        .chain(capture.clone())
+        .chain(capture.clone())
+        .into_log();
+
+    let count = AtomicUsize::new(0);
+    manual_log(&*logger, Info, Counted(&count));
+
+    (count.load(Ordering::SeqCst), capture.messages())
+}
+
+#[test]
+fn test_render_once() {
+    let (count, messages) = log_counted(true);
+    assert_eq!(count, 1);
+    assert_eq!(messages, ["[INFO] expensive"; 3]);
+}
+
+#[test]
+fn test_render_per_output() {
+    let (count, messages) = log_counted(false);
+    assert_eq!(count, 3);
+    assert_eq!(messages, ["[INFO] expensive"; 3]);
+}
+
+#[test]
+fn test_render_once_nested() {
+    let outer = Capture::new();
+    let inner = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .render_once(true)
+        .chain(outer.clone())
+        .chain(
+            fern::Dispatch::new()
+                .render_once(true)
+                .format(|out, message, _| out.finish(format_args!("<{}>", message)))
+                .chain(inner.clone()),
+        )
+        .into_log();
+
+    manual_log(&*logger, Info, "first");
+    manual_log(&*logger, Info, "second");
+
+    assert_eq!(outer.messages(), ["first", "second"]);
+    assert_eq!(inner.messages(), ["<first>", "<second>"]);
+}
+
+#[test]
+fn test_render_once_writer_outputs() {
+    let temp_log_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    let log_file = temp_log_dir.path().join("test.log");
+    let writer = SharedWriter::default();
+    let (tx, rx) = mpsc::channel();
+
+    let count = AtomicUsize::new(0);
+    {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .render_once(true)
+            .chain(fern::Output::writer(Box::new(writer.clone()), "\r\n"))
+            .chain(fern::Output::file(
+                fern::log_file(&log_file).expect("Failed to open log file"),
+                ";",
+            ))
+            .chain(fern::Output::sender(tx, "\n"))
+            .into_log();
+
+        manual_log(&*logger, Info, Counted(&count));
+        logger.flush();
+    }
+
+    // each output writes the message rendered for the first.
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+    assert_eq!(&*writer.0.lock().unwrap(), b"expensive\r\n");
+    assert_eq!(fs::read_to_string(&log_file).unwrap(), "expensive;");
+    assert_eq!(rx.recv().unwrap(), "expensive\n");
+
+    temp_log_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
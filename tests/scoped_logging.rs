@@ -0,0 +1,84 @@
+//! Tests for thread-local logger overrides.
+use fern::{testing::Capture, MemoryBuffer};
+
+#[test]
+fn test_scoped_overrides_global() {
+    let global = MemoryBuffer::new(100);
+    fern::scope::set_global(
+        fern::Dispatch::new()
+            .level(log::LevelFilter::Info)
+            .chain(global.clone()),
+    )
+    .unwrap();
+
+    let scoped = Capture::new();
+    fern::scoped(fern::Dispatch::new().chain(scoped.clone()), || {
+        log::debug!(target: "scoped_test", "inside");
+    });
+    log::info!(target: "scoped_test", "outside");
+
+    assert_eq!(scoped.messages(), ["inside"]);
+    // other tests run in parallel, and may log to the global dispatch too.
+    let outside = global.snapshot_target("scoped_test");
+    assert_eq!(outside.len(), 1);
+    assert_eq!(outside[0].message(), "outside");
+}
+
+#[test]
+fn test_scoped_nested() {
+    let outer = Capture::new();
+    let inner = Capture::new();
+
+    let result = fern::scoped(fern::Dispatch::new().chain(outer.clone()), || {
+        log::info!("one");
+        fern::scoped(fern::Dispatch::new().chain(inner.clone()), || {
+            log::info!("two");
+        });
+        log::info!("three");
+        42
+    });
+
+    assert_eq!(result, 42);
+    assert_eq!(outer.messages(), ["one", "three"]);
+    assert_eq!(inner.messages(), ["two"]);
+}
+
+#[test]
+fn test_scoped_spawned_threads() {
+    let buffer = Capture::new();
+
+    fern::scoped(fern::Dispatch::new().chain(buffer.clone()), || {
+        fern::scope::spawn(|| log::info!("propagated"))
+            .join()
+            .unwrap();
+        std::thread::spawn(|| log::info!(target: "not_propagated", "not propagated"))
+            .join()
+            .unwrap();
+
+        let handle = fern::scope::current();
+        std::thread::spawn(move || handle.enter(|| log::info!("entered")))
+            .join()
+            .unwrap();
+    });
+
+    assert_eq!(buffer.messages(), ["propagated", "entered"]);
+}
+
+#[test]
+fn test_scoped_restored_after_panic() {
+    let outer = Capture::new();
+    let inner = Capture::new();
+
+    fern::scoped(fern::Dispatch::new().chain(outer.clone()), || {
+        let result = std::panic::catch_unwind(|| {
+            fern::scoped(fern::Dispatch::new().chain(inner.clone()), || {
+                panic!("oops")
+            })
+        });
+        assert!(result.is_err());
+        log::info!("after panic");
+    });
+
+    assert!(inner.records().is_empty());
+    assert_eq!(outer.messages(), ["after panic"]);
+}
@@ -0,0 +1,16 @@
+//! Tests for thread-local logger overrides when another global logger is set.
+
+#[test]
+fn test_set_global_after_other_logger() {
+    fern::Dispatch::new()
+        .chain(fern::MemoryBuffer::new(10))
+        .apply()
+        .expect("Failed to set global logger");
+
+    // every attempt fails, not just the first.
+    assert!(fern::scope::set_global(fern::Dispatch::new()).is_err());
+    assert!(fern::scope::set_global(fern::Dispatch::new()).is_err());
+
+    let result = std::panic::catch_unwind(|| fern::scoped(fern::Dispatch::new(), || ()));
+    assert!(result.is_err());
+}
@@ -0,0 +1,46 @@
+//! Tests for flushing and closing the global logger.
+use std::fs;
+
+#[test]
+fn test_shutdown_flushes_and_closes() {
+    let temp_log_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    let log_file = temp_log_dir.path().join("test.log");
+    let buffer = fern::MemoryBuffer::new(10);
+
+    let shared = fern::Dispatch::new().chain(buffer.clone()).into_shared();
+
+    let handle = fern::Dispatch::new()
+        .level(log::LevelFilter::Info)
+        .chain(fern::log_file(&log_file).expect("Failed to open log file"))
+        .chain(fern::Dispatch::new().chain(shared))
+        .apply_with_shutdown()
+        .expect("Failed to set global logger");
+
+    log::info!("before shutdown");
+    assert!(!handle.is_shutdown());
+
+    handle.shutdown();
+    assert!(handle.is_shutdown());
+    assert_eq!(log::max_level(), log::LevelFilter::Off);
+
+    log::logger().log(
+        &log::RecordBuilder::new()
+            .args(format_args!("after shutdown"))
+            .level(log::Level::Error)
+            .build(),
+    );
+    // shutting down twice is fine.
+    handle.shutdown();
+    // and the logger keeps discarding records, rather than printing them.
+    handle.shutdown_to_stderr();
+    assert_eq!(log::max_level(), log::LevelFilter::Off);
+    assert!(!log::logger().enabled(&log::MetadataBuilder::new().level(log::Level::Error).build()));
+
+    let contents = fs::read_to_string(&log_file).unwrap();
+    assert_eq!(contents, "before shutdown\n");
+    assert_eq!(buffer.len(), 1);
+
+    temp_log_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
@@ -0,0 +1,61 @@
+//! Tests for shutting down the global logger while a record is being logged.
+use std::{
+    fmt, fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use fern::ShutdownHandle;
+
+/// Logs another record once the logger has been shut down.
+struct Reentrant {
+    started: Arc<AtomicBool>,
+    handle: ShutdownHandle,
+}
+
+impl fmt::Display for Reentrant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.started.store(true, Ordering::SeqCst);
+        while !self.handle.is_shutdown() {
+            thread::yield_now();
+        }
+        log::info!("nested");
+        write!(f, "outer")
+    }
+}
+
+#[test]
+fn test_shutdown_while_logging_reentrantly() {
+    let temp_log_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    let log_file = temp_log_dir.path().join("test.log");
+
+    let handle = fern::Dispatch::new()
+        .level(log::LevelFilter::Info)
+        .chain(fern::log_file(&log_file).expect("Failed to open log file"))
+        .apply_with_shutdown()
+        .expect("Failed to set global logger");
+
+    let started = Arc::new(AtomicBool::new(false));
+    let logging = {
+        let started = started.clone();
+        thread::spawn(move || log::info!("{}", Reentrant { started, handle }))
+    };
+    while !started.load(Ordering::SeqCst) {
+        thread::yield_now();
+    }
+
+    // waits for the record being logged, which logs another record meanwhile.
+    handle.shutdown();
+    logging.join().unwrap();
+
+    // the record being logged is written, while the one it logs is discarded.
+    let contents = fs::read_to_string(&log_file).unwrap();
+    assert_eq!(contents, "outer\n");
+
+    temp_log_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
@@ -0,0 +1,88 @@
+//! Tests for splitting records between stdout and stderr by level.
+use std::{env, process::Command};
+
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+/// Set in the child process, which logs to its real stdout and stderr.
+const CHILD_VAR: &str = "FERN_TEST_STD_SPLIT_CHILD";
+/// Marks lines written by the logger, so they can be told apart from the
+/// test harness' own output, which may start the same line.
+const PREFIX: &str = "std_split: ";
+
+/// Returns the lines written by the logger, each still ending in its
+/// separator.
+fn logged_lines(output: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(output)
+        .split_inclusive('\n')
+        .filter_map(|line| {
+            line.find(PREFIX)
+                .map(|i| line[i + PREFIX.len()..].to_owned())
+        })
+        .collect()
+}
+
+#[test]
+fn test_std_split_levels() {
+    if env::var_os(CHILD_VAR).is_some() {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .format(|out, message, record| {
+                out.finish(format_args!("{}{} {}", PREFIX, record.level(), message))
+            })
+            .chain(fern::Output::std_split(Warn))
+            .into_log();
+
+        for level in [Error, Warn, Info, Debug, Trace] {
+            manual_log(&*logger, level, "message");
+        }
+        logger.flush();
+        return;
+    }
+
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "test_std_split_levels", "--nocapture"])
+        .env(CHILD_VAR, "1")
+        .output()
+        .expect("failed to run test binary");
+    assert!(output.status.success(), "child failed: {:?}", output);
+
+    // the threshold itself, and anything more severe, goes to stderr.
+    assert_eq!(
+        logged_lines(&output.stderr),
+        ["ERROR message\n", "WARN message\n"]
+    );
+    assert_eq!(
+        logged_lines(&output.stdout),
+        ["INFO message\n", "DEBUG message\n", "TRACE message\n"]
+    );
+}
+
+#[test]
+fn test_std_split_line_sep() {
+    if env::var_os(CHILD_VAR).is_some() {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .format(|out, message, record| {
+                out.finish(format_args!("{}{} {}", PREFIX, record.level(), message))
+            })
+            .chain(fern::Output::std_split_with_line_sep(Warn, "\r\n"))
+            .into_log();
+
+        manual_log(&*logger, Error, "message");
+        manual_log(&*logger, Info, "message");
+        logger.flush();
+        return;
+    }
+
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "test_std_split_line_sep", "--nocapture"])
+        .env(CHILD_VAR, "1")
+        .output()
+        .expect("failed to run test binary");
+    assert!(output.status.success(), "child failed: {:?}", output);
+
+    assert_eq!(logged_lines(&output.stderr), ["ERROR message\r\n"]);
+    assert_eq!(logged_lines(&output.stdout), ["INFO message\r\n"]);
+}
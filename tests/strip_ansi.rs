@@ -0,0 +1,42 @@
+//! Tests for removing ANSI escape sequences from messages.
+use fern::{testing::Capture, Output};
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+#[test]
+fn test_strip_ansi() {
+    let stripped = Capture::new();
+    let unchanged = Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .chain(Output::strip_ansi(stripped.clone()))
+        .chain(unchanged.clone())
+        .into_log();
+
+    manual_log(
+        &*logger,
+        Info,
+        "\x1B[1;31mred\x1B[0m plain \x1B[38;5;208morange\x1B[m",
+    );
+    // sequences split across writes.
+    manual_log(
+        &*logger,
+        Info,
+        format_args!("{}{}{}{}", "a\x1B", "[3", "2mb\x1B[0", "m\x1B"),
+    );
+    // escapes which don't start a CSI sequence are kept.
+    manual_log(&*logger, Info, "\x1B\x1B]x é\x1B[");
+    manual_log(&*logger, Info, "no escapes");
+
+    assert_eq!(
+        stripped.messages(),
+        ["red plain orange", "ab\x1B", "\x1B\x1B]x é", "no escapes"]
+    );
+    assert_eq!(
+        unchanged.records()[0].message(),
+        "\x1B[1;31mred\x1B[0m plain \x1B[38;5;208morange\x1B[m"
+    );
+}
@@ -0,0 +1,80 @@
+//! Tests for customized syslog severities and facilities.
+#![cfg(all(not(windows), feature = "syslog-7"))]
+use std::os::unix::net::UnixDatagram;
+
+use fern::{SyslogFacility, SyslogMapping, SyslogSeverity};
+use log::Level::*;
+
+fn log_to(logger: &dyn log::Log, level: log::Level, target: &str, message: &str) {
+    logger.log(
+        &log::RecordBuilder::new()
+            .args(format_args!("{}", message))
+            .level(level)
+            .target(target)
+            .build(),
+    );
+}
+
+fn recv_priority(socket: &UnixDatagram) -> String {
+    let mut buf = [0; 1024];
+    let len = socket.recv(&mut buf).unwrap();
+    let message = String::from_utf8_lossy(&buf[..len]);
+    let end = message.find('>').expect("expected syslog priority");
+    message[1..end].to_owned()
+}
+
+#[test]
+fn test_syslog_mapping() {
+    let temp_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+    let socket_path = temp_dir.path().join("syslog.sock");
+    let socket = UnixDatagram::bind(&socket_path).unwrap();
+
+    {
+        let formatter = syslog7::Formatter3164 {
+            facility: syslog7::Facility::LOG_USER,
+            hostname: None,
+            process: "fern-test".to_owned(),
+            pid: 0,
+        };
+        let syslog = syslog7::unix_custom(formatter, &socket_path).unwrap();
+
+        let mapping = SyslogMapping::new()
+            .facility_for("app::auth", SyslogFacility::Auth)
+            .severity(|record| match (record.level(), record.target()) {
+                (Info, "app::lifecycle") => SyslogSeverity::Notice,
+                (Error, "app::db") => SyslogSeverity::Critical,
+                (level, _) => level.into(),
+            });
+
+        let (_max_level, logger) = fern::Dispatch::new()
+            .chain(fern::Output::syslog_mapped(syslog, mapping))
+            .into_log();
+
+        let l = &*logger;
+        // user (1) * 8 + informational (6)
+        log_to(l, Info, "app", "plain info");
+        assert_eq!(recv_priority(&socket), "14");
+        // user (1) * 8 + notice (5)
+        log_to(l, Info, "app::lifecycle", "started");
+        assert_eq!(recv_priority(&socket), "13");
+        // user (1) * 8 + critical (2)
+        log_to(l, Error, "app::db", "connection lost");
+        assert_eq!(recv_priority(&socket), "10");
+        // auth (4) * 8 + warning (4)
+        log_to(l, Warn, "app::auth::session", "bad password");
+        assert_eq!(recv_priority(&socket), "36");
+        // back to the logger's facility: user (1) * 8 + debug (7)
+        log_to(l, Trace, "app::authz", "checking");
+        assert_eq!(recv_priority(&socket), "15");
+    }
+
+    temp_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}
+
+#[test]
+#[should_panic(expected = "output is not a syslog logger")]
+fn test_syslog_mapping_rejects_other_outputs() {
+    fern::Output::syslog_mapped(std::io::stdout(), SyslogMapping::new());
+}
@@ -0,0 +1,95 @@
+//! Tests for the `fern::testing` capture harness.
+use std::{thread, time::Duration};
+
+use fern::{assert_logged, testing::Query};
+use log::Level::*;
+
+#[test]
+fn test_capture_is_per_test() {
+    let capture = fern::testing::capture();
+
+    for _ in 0..10 {
+        log::info!(target: "first", "from first test");
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_logged!(capture, Info, target = "first");
+    assert!(!capture.contains(&Query::new().target("second")));
+    assert_eq!(capture.records().len(), 10);
+}
+
+#[test]
+fn test_capture_is_per_test_too() {
+    let capture = fern::testing::capture();
+
+    for _ in 0..10 {
+        log::info!(target: "second", "from second test");
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_logged!(capture, Info, target = "second");
+    assert!(!capture.contains(&Query::new().target("first")));
+    assert_eq!(capture.records().len(), 10);
+}
+
+#[test]
+fn test_capture_record_fields() {
+    let capture = fern::testing::capture();
+
+    log::warn!(target: "db::pool", "query timeout after {}s", 30);
+    let line = line!() - 1;
+
+    assert_logged!(capture, Warn, target = "db", contains "timeout");
+    assert_logged!(capture, Warn, contains "30s",);
+
+    let records = capture.find(&Query::new().level(Warn));
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.target(), "db::pool");
+    assert_eq!(record.message(), "query timeout after 30s");
+    assert_eq!(record.file(), Some(file!()));
+    assert_eq!(record.line(), Some(line));
+}
+
+#[test]
+#[should_panic(expected = "no record matching level = ERROR")]
+fn test_assert_logged_fails() {
+    let capture = fern::testing::capture();
+
+    log::warn!(target: "db", "query timeout");
+
+    assert_logged!(capture, Error, target = "db");
+}
+
+#[cfg(feature = "kv")]
+#[test]
+fn test_capture_key_values() {
+    let capture = fern::testing::capture();
+
+    log::info!(request_id = 42; "handled");
+
+    let records = capture.records();
+    assert_eq!(records[0].key_value("request_id"), Some("42"));
+}
+
+#[test]
+fn test_capture_chained() {
+    let capture = fern::testing::Capture::new();
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .format(|out, msg, record| out.finish(format_args!("[{}] {}", record.level(), msg)))
+        .chain(capture.clone())
+        .into_log();
+
+    logger.log(
+        &log::RecordBuilder::new()
+            .args(format_args!("hello"))
+            .level(Debug)
+            .target("app")
+            .build(),
+    );
+
+    assert_logged!(capture, Debug, target = "app", contains "[DEBUG] hello");
+    capture.clear();
+    assert!(capture.records().is_empty());
+}
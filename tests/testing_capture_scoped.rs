@@ -0,0 +1,18 @@
+//! Tests for using the `fern::testing` capture harness alongside scopes.
+use fern::{assert_logged, testing::Query, MemoryBuffer};
+use log::Level::*;
+
+#[test]
+fn test_capture_with_scoped() {
+    let capture = fern::testing::capture();
+    let buffer = MemoryBuffer::new(10);
+
+    log::info!("outside the scope");
+    fern::scoped(fern::Dispatch::new().chain(buffer.clone()), || {
+        log::info!("inside the scope");
+    });
+
+    assert_logged!(capture, Info, contains "outside the scope");
+    assert!(!capture.contains(&Query::new().contains("inside the scope")));
+    assert_eq!(buffer.snapshot()[0].message(), "inside the scope");
+}
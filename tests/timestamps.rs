@@ -0,0 +1,97 @@
+//! Tests for formatting timestamps.
+use std::time::{Duration, UNIX_EPOCH};
+
+use fern::{
+    testing::Capture,
+    time::{Precision, Rfc3339},
+};
+use log::Level::*;
+
+mod support;
+
+use support::manual_log;
+
+#[test]
+fn test_utc_dates() {
+    let timestamps = Rfc3339::new();
+    let cases = [
+        (0, "1970-01-01T00:00:00Z"),
+        (1_518_568_087, "2018-02-14T00:28:07Z"),
+        // leap days.
+        (951_782_400, "2000-02-29T00:00:00Z"),
+        (1_709_251_199, "2024-02-29T23:59:59Z"),
+        (4_107_542_400, "2100-03-01T00:00:00Z"),
+        (253_402_300_799, "9999-12-31T23:59:59Z"),
+        // five-digit years aren't cached, but still formatted.
+        (253_402_300_800, "10000-01-01T00:00:00Z"),
+    ];
+    for (seconds, expected) in cases {
+        let time = UNIX_EPOCH + Duration::from_secs(seconds);
+        assert_eq!(timestamps.format(time).to_string(), expected);
+        // and again from the cache.
+        assert_eq!(timestamps.format(time).to_string(), expected);
+    }
+}
+
+#[test]
+fn test_before_epoch() {
+    let timestamps = Rfc3339::new().precision(Precision::Millis);
+    let cases = [
+        (Duration::from_millis(1), "1969-12-31T23:59:59.999Z"),
+        (Duration::from_secs(1), "1969-12-31T23:59:59.000Z"),
+        (
+            Duration::from_secs(86_400 * 365),
+            "1969-01-01T00:00:00.000Z",
+        ),
+    ];
+    for (before, expected) in cases {
+        assert_eq!(timestamps.format(UNIX_EPOCH - before).to_string(), expected);
+    }
+}
+
+#[test]
+fn test_precision() {
+    let time = UNIX_EPOCH + Duration::new(1_518_568_087, 123_456_789);
+    let cases = [
+        (Precision::Seconds, "2018-02-14T00:28:07Z"),
+        (Precision::Millis, "2018-02-14T00:28:07.123Z"),
+        (Precision::Micros, "2018-02-14T00:28:07.123456Z"),
+        (Precision::Nanos, "2018-02-14T00:28:07.123456789Z"),
+    ];
+    for (precision, expected) in cases {
+        let timestamps = Rfc3339::new().precision(precision);
+        assert_eq!(timestamps.format(time).to_string(), expected);
+    }
+
+    let time = UNIX_EPOCH + Duration::new(1_518_568_087, 5_000);
+    let timestamps = Rfc3339::new().precision(Precision::Micros);
+    assert_eq!(
+        timestamps.format(time).to_string(),
+        "2018-02-14T00:28:07.000005Z"
+    );
+}
+
+#[test]
+fn test_formatting_records() {
+    let capture = Capture::new();
+    let time = UNIX_EPOCH + Duration::new(1_518_568_087, 900_000_000);
+    let timestamps = Rfc3339::new().precision(Precision::Millis);
+
+    let (_max_level, logger) = fern::Dispatch::new()
+        .format(move |out, message, _| {
+            out.finish(format_args!("[{}] {}", timestamps.format(time), message))
+        })
+        .chain(capture.clone())
+        .into_log();
+
+    manual_log(&*logger, Info, "message");
+
+    assert_eq!(
+        capture.records()[0].message(),
+        "[2018-02-14T00:28:07.900Z] message"
+    );
+    // the current time is formatted in the same shape.
+    let now = Rfc3339::new().now().to_string();
+    assert_eq!(now.len(), "2018-02-14T00:28:07Z".len());
+    assert!(now.ends_with('Z'));
+}
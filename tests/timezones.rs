@@ -0,0 +1,137 @@
+//! Tests for formatting timestamps and naming files in other time zones.
+#![cfg(feature = "timezone")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use fern::time::{Precision, Rfc3339, Timezone, UtcOffset};
+
+fn at(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+#[test]
+fn test_parse_offsets() {
+    let cases = [
+        ("Z", 0),
+        ("+00:00", 0),
+        ("+05:30", 19_800),
+        ("+0530", 19_800),
+        ("-08", -28_800),
+        ("-03:30", -12_600),
+        ("+23:59", 86_340),
+    ];
+    for (text, seconds) in cases {
+        let offset: UtcOffset = text.parse().unwrap();
+        assert_eq!(offset.seconds(), seconds, "parsing {:?}", text);
+    }
+    for invalid in [
+        "", "05:30", "+5:30", "+24:00", "+05:60", "+05:+3", "+05:3é", "UTC",
+    ] {
+        assert!(
+            invalid.parse::<UtcOffset>().is_err(),
+            "parsing {:?}",
+            invalid
+        );
+    }
+
+    assert_eq!(
+        UtcOffset::from_seconds(-12_600).unwrap().to_string(),
+        "-03:30"
+    );
+    assert_eq!(
+        UtcOffset::from_seconds(3_208).unwrap().to_string(),
+        "+00:53:28"
+    );
+    assert_eq!(UtcOffset::from_seconds(86_400), None);
+}
+
+#[test]
+fn test_fixed_offsets() {
+    let time = at(1_518_568_087);
+    let cases = [
+        ("+05:30", "2018-02-14T05:58:07+05:30"),
+        ("-08:00", "2018-02-13T16:28:07-08:00"),
+        ("+00:00", "2018-02-14T00:28:07+00:00"),
+    ];
+    for (offset, expected) in cases {
+        let timestamps = Rfc3339::new().timezone(offset.parse::<UtcOffset>().unwrap());
+        assert_eq!(timestamps.format(time).to_string(), expected);
+    }
+
+    let timestamps = Rfc3339::new()
+        .precision(Precision::Millis)
+        .timezone(UtcOffset::from_seconds(-3_600).unwrap());
+    assert_eq!(
+        timestamps.format(at(0)).to_string(),
+        "1969-12-31T23:00:00.000-01:00"
+    );
+}
+
+#[test]
+fn test_posix_rules() {
+    let berlin = Timezone::posix("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+    let timestamps = Rfc3339::new().timezone(berlin.clone());
+    // daylight saving time starts at 2024-03-31T01:00:00Z, and ends at
+    // 2024-10-27T01:00:00Z.
+    let cases = [
+        (1_711_846_799, "2024-03-31T01:59:59+01:00"),
+        (1_711_846_800, "2024-03-31T03:00:00+02:00"),
+        (1_729_990_799, "2024-10-27T02:59:59+02:00"),
+        (1_729_990_800, "2024-10-27T02:00:00+01:00"),
+    ];
+    for (seconds, expected) in cases {
+        assert_eq!(timestamps.format(at(seconds)).to_string(), expected);
+    }
+    assert_eq!(berlin.offset_at(at(1_711_846_800)).seconds(), 7200);
+
+    // offsets with seconds are truncated to whole minutes.
+    let timestamps = Rfc3339::new().timezone(Timezone::posix("<LMT>-0:53:28").unwrap());
+    assert_eq!(
+        timestamps.format(at(0)).to_string(),
+        "1970-01-01T00:53:00+00:53"
+    );
+
+    assert!(Timezone::posix("CET-1CEST,M3.5.0").is_err());
+}
+
+#[test]
+fn test_local_time_from_tz() {
+    // this is the only test in this file reading local time, so it's loaded
+    // after this is set.
+    std::env::set_var("TZ", "<+0930>-9:30");
+
+    let timestamps = Rfc3339::new().timezone(Timezone::local());
+    assert_eq!(
+        timestamps.format(at(1_518_568_087)).to_string(),
+        "2018-02-14T09:58:07+09:30"
+    );
+}
+
+#[cfg(feature = "date-based")]
+#[test]
+fn test_date_based_timezone() {
+    let temp_log_dir = tempfile::tempdir().expect("Failed to set up temporary directory");
+
+    {
+        let (_max_level, logger) = fern::Dispatch::new()
+            .chain(
+                fern::DateBased::new(temp_log_dir.path().join("offset."), "%z.log")
+                    .timezone("-03:30".parse::<UtcOffset>().unwrap()),
+            )
+            .into_log();
+        logger.log(
+            &log::RecordBuilder::new()
+                .args(format_args!("message"))
+                .level(log::Level::Info)
+                .build(),
+        );
+        logger.flush();
+    }
+
+    let contents = std::fs::read_to_string(temp_log_dir.path().join("offset.-0330.log"))
+        .expect("expected log file named with the offset");
+    assert_eq!(contents, "message\n");
+
+    temp_log_dir
+        .close()
+        .expect("Failed to clean up temporary directory");
+}